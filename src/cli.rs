@@ -1,5 +1,9 @@
 // src/cli.rs
-use clap::{Parser, Subcommand};
+use crate::constants::DEFAULT_WATCH_INTERVAL_MINUTES;
+use crate::modules::api::parse_semester_code;
+use crate::modules::output::OutputFormat;
+use crate::modules::whatif::{parse_simulation, parse_target, Simulation, Target};
+use clap::{Args, Parser, Subcommand};
 
 #[derive(Parser)]
 #[command(name = "uqgrd")]
@@ -16,10 +20,117 @@ pub enum Commands {
         #[arg(long, short = 's')]
         skip_encryption: bool,
     },
-    Grades {
-        /// Automatically select the current semester based on today's date
-        #[arg(long, short = 'c')]
-        current: bool,
+    Grades(GradesArgs),
+    /// Show the evaluation breakdown of a course
+    Course {
+        /// Course code, e.g. INF1120
+        sigle: String,
+        /// Semester to look in (defaults to the latest one with this course)
+        #[arg(long, short = 's', value_parser = parse_semester)]
+        semester: Option<u32>,
     },
+    /// Compute per-semester and cumulative GPA (UQAM 4.3 scale)
+    Gpa {
+        /// Compute a separate GPA for each program
+        #[arg(long, short = 'p')]
+        by_program: bool,
+    },
+    /// Show the full academic history across all semesters
+    History {
+        /// First year to include (e.g. 2023)
+        #[arg(long)]
+        from: Option<u32>,
+        /// Last year to include
+        #[arg(long)]
+        to: Option<u32>,
+        /// Only show this program code
+        #[arg(long)]
+        program: Option<String>,
+    },
+    /// Compute the score needed on the remaining evaluations of a course
+    Whatif {
+        /// Course code, e.g. INF1120
+        sigle: String,
+        /// Letter (A+ ... D) or percentage to aim for; all letters when omitted
+        #[arg(long, short = 't', value_parser = parse_target)]
+        target: Option<Target>,
+        /// Hypothetical score for an unposted evaluation, e.g. --simulate Final=85
+        #[arg(long, value_parser = parse_simulation)]
+        simulate: Vec<Simulation>,
+        /// Semester to look in (defaults to the latest one with this course)
+        #[arg(long, short = 's', value_parser = parse_semester)]
+        semester: Option<u32>,
+    },
+    /// Compare the daemon's saved state with the live portal (read-only)
+    Diff {
+        /// Semester to compare (defaults to the current one, like the daemon)
+        #[arg(long, short = 's', value_parser = parse_semester)]
+        semester: Option<u32>,
+    },
+    /// Show how course totals and letters evolved, from the daemon's history
+    Timeline {
+        /// Course code, e.g. INF1120 (all courses of the semester when omitted)
+        sigle: Option<String>,
+        /// Semester to show (defaults to the latest one recorded)
+        #[arg(long, short = 's', value_parser = parse_semester)]
+        semester: Option<u32>,
+    },
+    /// Browse grades in a full-screen terminal interface
+    Tui,
     Start,
+    /// Run a single check cycle and exit (for cron, systemd timers, CronJobs)
+    Check,
+    /// Manage the daemon's saved grade state
+    State {
+        #[command(subcommand)]
+        action: StateAction,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum StateAction {
+    /// Clear the saved state so the next check records a fresh baseline
+    Reset {
+        /// Record the current grades as the baseline now, without notifying
+        #[arg(long)]
+        baseline: bool,
+    },
+}
+
+#[derive(Args)]
+pub struct GradesArgs {
+    /// Automatically select the current semester based on today's date
+    #[arg(long, short = 'c', conflicts_with = "semester")]
+    pub current: bool,
+    /// Semester to display: 20253, A2025, "Automne 2025", H26...
+    #[arg(long, short = 's', value_parser = parse_semester)]
+    pub semester: Option<u32>,
+    /// Program code to display when enrolled in several (e.g. 7316); also
+    /// limits the interactive menu
+    #[arg(long)]
+    pub program: Option<String>,
+    /// Output format
+    #[arg(long, short = 'f', value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+    /// Stay in the foreground and refresh the table on an interval
+    #[arg(long, short = 'w')]
+    pub watch: bool,
+    /// Minutes between refreshes in watch mode
+    #[arg(long, requires = "watch", default_value_t = DEFAULT_WATCH_INTERVAL_MINUTES)]
+    pub interval: u64,
+    /// Ring the terminal bell when a grade changes (watch mode)
+    #[arg(long, requires = "watch")]
+    pub bell: bool,
+    /// Show a desktop notification when a grade changes (watch mode)
+    #[arg(long, requires = "watch")]
+    pub notify: bool,
+}
+
+fn parse_semester(input: &str) -> Result<u32, String> {
+    parse_semester_code(input).ok_or_else(|| {
+        format!(
+            "'{}' is not a semester (try 20253, A2025, \"Automne 2025\" or H26)",
+            input
+        )
+    })
 }
@@ -1,3 +1,53 @@
 pub const APP_NAME: &str = "uqgrd";
 pub const DEFAULT_CHECK_INTERVAL_MINUTES: u64 = 60; // Check every hour
+pub const DEFAULT_START_DELAY_SECS: u64 = 0; // First check runs right away
+pub const DEFAULT_CHECK_JITTER_SECS: u64 = 0; // Random extra delay per check
 pub const STATE_FILE: &str = "grades_state.json";
+pub const HISTORY_FILE: &str = "grades_history.jsonl";
+pub const STATE_LOCK_FILE: &str = "state.lock"; // Held while reading or writing state
+pub const DAEMON_LOCK_FILE: &str = "daemon.lock"; // Held for the daemon's lifetime
+pub const DEFAULT_STATE_LOCK_TIMEOUT_SECS: u64 = 30;
+
+// --- NOTIFICATION OUTBOX ---
+pub const OUTBOX_FILE: &str = "outbox.json";
+pub const OUTBOX_RETRY_BASE_MINUTES: i64 = 5;
+pub const OUTBOX_RETRY_MAX_MINUTES: i64 = 360;
+pub const OUTBOX_DELIVERED_RETENTION_DAYS: i64 = 30; // Delivered keys kept for deduplication
+pub const MAX_CHANGE_TIMESTAMPS: usize = 50; // Per course, for adaptive polling
+
+// --- API ---
+pub const DEFAULT_BASE_URL: &str = "https://monportail.uqam.ca";
+pub const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
+pub const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+pub const USER_AGENT: &str = concat!("uqgrd/", env!("CARGO_PKG_VERSION"));
+
+// --- RETRY ---
+pub const DEFAULT_RETRY_MAX_ATTEMPTS: u32 = 4;
+pub const DEFAULT_RETRY_BASE_DELAY_MS: u64 = 500;
+pub const DEFAULT_RETRY_MAX_DELAY_MS: u64 = 30_000;
+pub const DEFAULT_RETRY_STATUSES: &str = "408,429,500,502,503,504";
+
+// --- CONCURRENCY ---
+pub const DEFAULT_MAX_CONCURRENCY: usize = 4; // Course detail requests in flight
+
+// --- GPA ---
+pub const DEFAULT_COURSE_CREDITS: f64 = 3.0; // Standard UQAM course weight
+
+// --- WATCH MODE ---
+pub const DEFAULT_WATCH_INTERVAL_MINUTES: u64 = 5;
+
+// --- ONE-SHOT CHECK EXIT CODES ---
+// Kept clear of the error exit codes (3-12)
+pub const EXIT_CHANGES_NOTIFIED: i32 = 20;
+pub const EXIT_PARTIAL_FAILURE: i32 = 21;
+pub const EXIT_CHANGES_DEFERRED: i32 = 22; // Queued, not sent yet (quiet hours, retry delay)
+
+// --- ADAPTIVE POLLING ---
+pub const DEFAULT_ADAPTIVE_FAST_MINUTES: u64 = 15;
+pub const DEFAULT_ADAPTIVE_SLOW_MINUTES: u64 = 240;
+pub const DEFAULT_ADAPTIVE_RECENT_HOURS: i64 = 48;
+// How close (in days of the year) a past change must be to count as a pattern
+pub const ADAPTIVE_LEARN_DAYS: u32 = 7;
+// MM-DD:MM-DD ranges around midterms and finals of the fall, winter and summer sessions
+pub const DEFAULT_POSTING_PERIODS: &str =
+    "10-15:11-10,12-05:01-10,02-20:03-20,04-15:05-10,06-10:07-05,08-01:08-25";
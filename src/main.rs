@@ -3,147 +3,382 @@ mod constants;
 mod modules;
 
 use clap::Parser;
-use cli::{Cli, Commands};
+use cli::{Cli, Commands, GradesArgs, StateAction};
+use modules::api::{Activity, ApiClient, DetailActivity, Programme, SemesterResult};
+use modules::error::UqgrdError;
+use modules::output::{self, OutputFormat};
+use modules::watch::WatchOptions;
+use modules::whatif::{Projection, Simulation, Target};
+use std::process;
+use std::time::Duration;
 
 #[tokio::main]
 async fn main() {
     let args = Cli::parse();
 
-    match args.command {
+    if let Err(e) = run(args.command).await {
+        eprintln!("❌ {}", e);
+        process::exit(e.exit_code());
+    }
+}
+
+async fn run(command: Commands) -> Result<(), UqgrdError> {
+    match command {
         Commands::Credentials { skip_encryption } => {
-            match modules::interact::prompt_credentials() {
-                Ok((username, password)) => {
-                    // Save credentials with the optional encryption flag
-                    if let Err(e) =
-                        modules::auth::save_credentials(&username, &password, skip_encryption)
-                    {
-                        eprintln!("Error saving credentials: {}", e);
-                    }
-                }
-                Err(e) => eprintln!("Error getting input: {}", e),
+            let (username, password) = modules::interact::prompt_credentials()?;
+            // Save credentials with the optional encryption flag
+            modules::auth::save_credentials(&username, &password, skip_encryption)?;
+        }
+        Commands::Grades(args) => grades(&args).await?,
+        Commands::Course { sigle, semester } => course(&sigle, semester).await?,
+        Commands::Whatif {
+            sigle,
+            target,
+            simulate,
+            semester,
+        } => whatif(&sigle, target, &simulate, semester).await?,
+        Commands::Gpa { by_program } => gpa(by_program).await?,
+        Commands::History { from, to, program } => history(from, to, program.as_deref()).await?,
+        Commands::Diff { semester } => diff(semester).await?,
+        Commands::Timeline { sigle, semester } => timeline(sigle.as_deref(), semester)?,
+        Commands::Tui => {
+            let client = connect().await?;
+            modules::tui::run(client).await?;
+        }
+        Commands::Start => {
+            modules::daemon::start_daemon().await?;
+        }
+        Commands::State {
+            action: StateAction::Reset { baseline },
+        } => modules::daemon::reset_state(baseline).await?,
+        Commands::Check => {
+            let report = modules::daemon::check_and_notify().await?;
+            println!(
+                "✅ Check complete: {} notified, {} deferred, {} failed",
+                report.notified, report.deferred, report.failures
+            );
+            let code = report.exit_code();
+            if code != 0 {
+                process::exit(code);
             }
         }
-        Commands::Grades { current } => {
-            // 1. Credentials
-            let (username, password) = match modules::auth::get_credentials() {
-                Ok(c) => c,
-                Err(e) => {
-                    eprintln!("❌ {}", e);
-                    return;
-                }
-            };
+    }
 
-            // 2. Auth
-            let token = match modules::api::get_token(&username, &password).await {
-                Ok(t) => t,
-                Err(e) => {
-                    eprintln!("❌ {}", e);
-                    return;
-                }
-            };
+    Ok(())
+}
 
-            // 3. Fetch Transcript
-            let transcript = match modules::api::fetch_transcript(&token).await {
-                Ok(t) => t,
-                Err(e) => {
-                    eprintln!("❌ {}", e);
-                    return;
-                }
-            };
+// Credentials + login, shared by every command that talks to the portal
+async fn connect() -> Result<ApiClient, UqgrdError> {
+    let (username, password) = modules::auth::get_credentials()?;
 
-            // 4. Select Semester (Auto vs Manual)
-            let selected_pair = if current {
-                // LOGIC: Calculate current code
-                let current_code = modules::api::get_current_semester_code();
-                let sem_name = modules::api::format_semester_name(current_code);
-
-                println!("📅 Date detected: {}", sem_name);
-
-                // Try to find exact match
-                let match_found = transcript.iter().find(|s| s.trimestre == current_code);
-
-                match match_found {
-                    Some(sem) => {
-                        println!("✅ Found current semester in transcript.");
-                        // Default to the first program in that semester
-                        sem.programmes.first().map(|p| (sem, p))
-                    }
-                    None => {
-                        println!(
-                            "⚠️  Current semester ({}) not found in transcript.",
-                            sem_name
-                        );
-                        println!("👉 Falling back to latest available semester.");
-                        // Fallback to the first one (Latest, assuming sorted)
-                        transcript
-                            .first()
-                            .and_then(|sem| sem.programmes.first().map(|p| (sem, p)))
-                    }
-                }
-            } else {
-                // Manual selection
-                match modules::interact::select_semester(&transcript) {
-                    Ok(opt) => opt,
-                    Err(e) => {
-                        eprintln!("❌ {}", e);
-                        return;
-                    }
-                }
-            };
+    let mut client = ApiClient::from_env()?;
+    client.login(&username, &password).await?;
+    Ok(client)
+}
+
+async fn grades(args: &GradesArgs) -> Result<(), UqgrdError> {
+    if args.watch && args.format != OutputFormat::Table {
+        return Err(UqgrdError::Input(
+            "--watch only supports the table format".to_string(),
+        ));
+    }
 
-            // 5. Display Grades (Common logic)
-            if let Some((sem_result, program)) = selected_pair {
-                println!(
-                    "\n📖 Grades for: {} - {}\n",
-                    modules::api::format_semester_name(sem_result.trimestre),
-                    program.titre_programme
-                );
-
-                println!(
-                    "{:<10} | {:<40} | {:<10} | {:<5}",
-                    "Sigle", "Title", "Total (%)", "Grade"
-                );
-                println!("{:-<10}-|-{:-<40}-|-{:-<10}-|-{:-<5}", "", "", "", "");
-
-                for activity in &program.activites {
-                    let details = modules::api::fetch_course_details(
-                        &token,
-                        sem_result.trimestre,
-                        &activity.sigle,
-                        activity.groupe,
-                    )
-                    .await;
-
-                    match details {
-                        Ok(det) => {
-                            let total_str = match det.total {
-                                Some(val) => format!("{:.2}%", val),
-                                None => "N/A".to_string(),
-                            };
-                            let note_str = det.note.unwrap_or_else(|| "N/A".to_string());
-                            println!(
-                                "{:<10} | {:<40} | {:<10} | {:<5}",
-                                activity.sigle,
-                                activity.titre.chars().take(40).collect::<String>(),
-                                total_str,
-                                note_str
-                            );
-                        }
-                        Err(_) => {
-                            println!(
-                                "{:<10} | {:<40} | {:<10} | {:<5}",
-                                activity.sigle, activity.titre, "ERROR", "---"
-                            );
-                        }
-                    }
+    // 1-2. Credentials & Auth
+    let client = connect().await?;
+
+    // 3. Fetch Transcript
+    let transcript = client.fetch_transcript().await?;
+
+    // Keep stdout clean for machine-readable formats
+    let table = args.format == OutputFormat::Table;
+    let notice = |msg: String| {
+        if table {
+            println!("{}", msg);
+        } else {
+            eprintln!("{}", msg);
+        }
+    };
+
+    // 4. Select Semester (Explicit vs Auto vs Manual)
+    let program = args.program.as_deref();
+    let selected_pair = if let Some(code) = args.semester {
+        let sem = transcript
+            .iter()
+            .find(|s| s.trimestre == code)
+            .ok_or_else(|| {
+                UqgrdError::Input(format!(
+                    "{} not found in transcript",
+                    modules::api::format_semester_name(code)
+                ))
+            })?;
+        Some((sem, pick_program(sem, program)?))
+    } else if args.current {
+        // LOGIC: Calculate current code
+        let current_code = modules::api::get_current_semester_code();
+        let sem_name = modules::api::format_semester_name(current_code);
+
+        notice(format!("📅 Date detected: {}", sem_name));
+
+        // Try to find exact match
+        let match_found = transcript.iter().find(|s| s.trimestre == current_code);
+
+        match match_found {
+            Some(sem) => {
+                notice("✅ Found current semester in transcript.".to_string());
+                // Default to the first program in that semester
+                Some((sem, pick_program(sem, program)?))
+            }
+            None => {
+                notice(format!(
+                    "⚠️  Current semester ({}) not found in transcript.",
+                    sem_name
+                ));
+                notice("👉 Falling back to latest available semester.".to_string());
+                // Fallback to the first one (Latest, assuming sorted)
+                match transcript.first() {
+                    Some(sem) => Some((sem, pick_program(sem, program)?)),
+                    None => None,
                 }
-                println!("\n");
-            } else {
-                println!("No selection made.");
             }
         }
-        Commands::Start => {
-            modules::daemon::start_daemon().await;
+    } else {
+        // Manual selection
+        modules::interact::select_semester(&transcript, program)?
+    };
+
+    // 5. Display Grades (Common logic)
+    if let Some((sem_result, program)) = selected_pair {
+        if args.watch {
+            let options = WatchOptions {
+                interval: Duration::from_secs(args.interval.max(1) * 60),
+                bell: args.bell,
+                notify: args.notify,
+            };
+            return modules::watch::run(&client, sem_result.trimestre, program, &options).await;
+        }
+
+        notice(format!(
+            "\n📖 Grades for: {} - {}\n",
+            modules::api::format_semester_name(sem_result.trimestre),
+            program.titre_programme
+        ));
+
+        // Fetched concurrently, printed in the transcript's course order
+        let all_details = client
+            .fetch_all_course_details(sem_result.trimestre, &program.activites)
+            .await;
+
+        if table {
+            modules::display::print_grades_table(&program.activites, &all_details, &[]);
+            println!("\n");
+        } else {
+            let records = output::grade_records(sem_result.trimestre, program, &all_details);
+            print!("{}", output::render(args.format, &records)?);
+        }
+    } else {
+        notice("No selection made.".to_string());
+    }
+
+    Ok(())
+}
+
+// `--program` when given, otherwise the first program of the semester
+fn pick_program<'a>(
+    sem: &'a SemesterResult,
+    program: Option<&str>,
+) -> Result<&'a Programme, UqgrdError> {
+    let found = match program {
+        Some(code) => sem.programmes.iter().find(|p| p.code_prog == code),
+        None => sem.programmes.first(),
+    };
+
+    found.ok_or_else(|| {
+        UqgrdError::Input(format!(
+            "Program {} not found in {}",
+            program.unwrap_or("(any)"),
+            modules::api::format_semester_name(sem.trimestre)
+        ))
+    })
+}
+
+// Transcript entry + live details of one course, shared by `course` and `whatif`
+async fn load_course(
+    sigle: &str,
+    semester: Option<u32>,
+) -> Result<(u32, Activity, DetailActivity), UqgrdError> {
+    let client = connect().await?;
+    let mut transcript = client.fetch_transcript().await?;
+    if let Some(code) = semester {
+        transcript.retain(|s| s.trimestre == code);
+    }
+
+    let (sem, _, activity) = modules::api::find_course(&transcript, sigle)
+        .ok_or_else(|| UqgrdError::Input(format!("Course {} not found in transcript", sigle)))?;
+
+    let detail = client
+        .fetch_course_details(sem.trimestre, &activity.sigle, activity.groupe)
+        .await?;
+
+    Ok((sem.trimestre, activity.clone(), detail))
+}
+
+async fn course(sigle: &str, semester: Option<u32>) -> Result<(), UqgrdError> {
+    let (trimestre, activity, detail) = load_course(sigle, semester).await?;
+
+    modules::display::print_course_detail(trimestre, &activity, &detail);
+    Ok(())
+}
+
+async fn whatif(
+    sigle: &str,
+    target: Option<Target>,
+    simulations: &[Simulation],
+    semester: Option<u32>,
+) -> Result<(), UqgrdError> {
+    let (_, activity, detail) = load_course(sigle, semester).await?;
+
+    if detail.evaluations.is_empty() {
+        return Err(UqgrdError::Input(format!(
+            "No evaluations published for {} yet",
+            activity.sigle
+        )));
+    }
+
+    let projection = Projection::new(&detail.evaluations, simulations)?;
+    let targets = match target {
+        Some(t) => vec![t],
+        None => modules::whatif::LETTER_CUTOFFS
+            .iter()
+            .map(|(label, cutoff)| Target {
+                label: label.to_string(),
+                cutoff: *cutoff,
+            })
+            .collect(),
+    };
+
+    modules::display::print_whatif(&activity, &projection, &targets, simulations);
+    Ok(())
+}
+
+// Dry run of a daemon cycle: nothing is sent and the state file is left untouched
+async fn diff(semester: Option<u32>) -> Result<(), UqgrdError> {
+    let first_run = modules::daemon::FirstRun::from_env()?;
+    let state = {
+        let _lock = modules::state::DirLock::state().await?;
+        modules::state::load_state()?
+    };
+    let client = connect().await?;
+    let transcript = client.fetch_transcript().await?;
+
+    let code = semester.unwrap_or_else(modules::api::get_current_semester_code);
+    let sem = transcript
+        .iter()
+        .find(|s| s.trimestre == code)
+        .ok_or_else(|| {
+            UqgrdError::Input(format!(
+                "{} not found in transcript",
+                modules::api::format_semester_name(code)
+            ))
+        })?;
+
+    println!(
+        "\n🔍 Saved state vs live grades for {} ({} course(s) tracked)\n",
+        modules::api::format_semester_name(code),
+        state.len()
+    );
+
+    for program in &sem.programmes {
+        println!("📚 {} - {}\n", program.code_prog, program.titre_programme);
+        let details = client
+            .fetch_all_course_details(sem.trimestre, &program.activites)
+            .await;
+        modules::display::print_state_diff(
+            &state,
+            first_run,
+            sem.trimestre,
+            &program.activites,
+            &details,
+        );
+        println!();
+    }
+
+    Ok(())
+}
+
+// Offline: reads only the history file written by the daemon
+fn timeline(sigle: Option<&str>, semester: Option<u32>) -> Result<(), UqgrdError> {
+    let mut entries = modules::history::load()?;
+
+    if let Some(code) = sigle {
+        entries.retain(|e| e.key.sigle.eq_ignore_ascii_case(code));
+    }
+    // A single course spans every semester it was taken; otherwise show one semester
+    let semester = semester.or_else(|| {
+        sigle
+            .is_none()
+            .then(|| entries.iter().map(|e| e.key.trimestre).max())
+            .flatten()
+    });
+    if let Some(code) = semester {
+        entries.retain(|e| e.key.trimestre == code);
+    }
+
+    if entries.is_empty() {
+        println!("No grade history recorded yet for these filters.");
+        println!("👉 The daemon (`uqgrd start` or `uqgrd check`) records every change it detects.");
+        return Ok(());
+    }
+
+    modules::display::print_timeline(&entries);
+    Ok(())
+}
+
+async fn gpa(by_program: bool) -> Result<(), UqgrdError> {
+    let client = connect().await?;
+    let transcript = client.fetch_transcript().await?;
+
+    println!("\n🎓 GPA (UQAM 4.3 scale)\n");
+
+    if by_program {
+        for (code, title) in modules::gpa::programs(&transcript) {
+            println!("📚 {} - {}\n", code, title);
+            let rows = modules::gpa::by_semester(&transcript, Some(&code));
+            modules::display::print_gpa_table(&rows);
+        }
+    } else {
+        let rows = modules::gpa::by_semester(&transcript, None);
+        modules::display::print_gpa_table(&rows);
+    }
+
+    Ok(())
+}
+
+async fn history(
+    from: Option<u32>,
+    to: Option<u32>,
+    program: Option<&str>,
+) -> Result<(), UqgrdError> {
+    let client = connect().await?;
+    let mut transcript = client.fetch_transcript().await?;
+
+    // Semester codes are YYYYT, so the year is the code without its last digit
+    transcript.retain(|s| {
+        let year = s.trimestre / 10;
+        from.is_none_or(|f| year >= f) && to.is_none_or(|t| year <= t)
+    });
+    if let Some(code) = program {
+        for sem in &mut transcript {
+            sem.programmes.retain(|p| p.code_prog == code);
         }
+        transcript.retain(|s| !s.programmes.is_empty());
     }
+
+    if transcript.is_empty() {
+        println!("No semesters match these filters.");
+        return Ok(());
+    }
+
+    println!("\n📚 Academic history");
+    modules::display::print_history(&transcript);
+    Ok(())
 }
@@ -1,11 +1,29 @@
-use chrono::{Datelike, Local};
-use reqwest::Client;
-use serde::Deserialize;
-use serde_json::json;
-
-const AUTH_ENDPOINT: &str = "https://monportail.uqam.ca/authentification";
-const RESUME_ENDPOINT: &str = "https://monportail.uqam.ca/apis/resumeResultat/identifiant";
-const DETAIL_ENDPOINT: &str = "https://monportail.uqam.ca/apis/resultatActivite/identifiant";
+use crate::constants::{
+    DEFAULT_BASE_URL, DEFAULT_CONNECT_TIMEOUT_SECS, DEFAULT_MAX_CONCURRENCY,
+    DEFAULT_REQUEST_TIMEOUT_SECS, USER_AGENT,
+};
+use crate::modules::auth;
+use crate::modules::common::{self, Log};
+use crate::modules::error::UqgrdError;
+use crate::modules::retry::RetryPolicy;
+use chrono::{Datelike, Local, NaiveDate};
+use futures::stream::{self, StreamExt};
+use reqwest::{Client, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use serde_json::{json, Value};
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap};
+use std::env;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+// Paths relative to the portal base URL (see `ApiClient::from_env`)
+const AUTH_PATH: &str = "/authentification";
+const RESUME_PATH: &str = "/apis/resumeResultat/identifiant";
+const DETAIL_PATH: &str = "/apis/resultatActivite/identifiant";
 
 // --- AUTH STRUCTS ---
 
@@ -35,23 +53,126 @@ pub struct SemesterResult {
 #[derive(Deserialize, Debug, Clone)]
 pub struct Programme {
     #[serde(rename = "codeProg")]
-    #[allow(dead_code)] // Suppress unused warning
     pub code_prog: String,
     #[serde(rename = "titreProgramme")]
     pub titre_programme: String,
     pub activites: Vec<Activity>,
+    // Fields we don't model yet, reported by `fetch_transcript`
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
+#[serde(from = "RawActivity")]
 pub struct Activity {
-    #[serde(rename = "sigle")]
     pub sigle: String,
-    #[serde(rename = "titreActivite")]
     pub titre: String,
-    #[allow(dead_code)] // Suppress unused warning (we use DetailActivity's note)
+    // Final letter grade from the transcript (None while the course is in progress)
     pub note: Option<String>,
-    #[serde(rename = "groupe")]
     pub groupe: u32,
+    pub credits: Option<f64>,
+    pub statut: Option<ActivityStatus>,
+    /// Distinction noted on the transcript (e.g. "Excellence").
+    pub mention: Option<String>,
+    /// Kind of activity (cours, stage, projet...).
+    pub type_activite: Option<String>,
+    pub date_debut: Option<NaiveDate>,
+    pub date_fin: Option<NaiveDate>,
+    // Fields we don't model yet, reported by `fetch_transcript`
+    pub extra: HashMap<String, Value>,
+    // Modeled fields left empty because of an unexpected shape, reported too
+    pub malformed: Vec<&'static str>,
+}
+
+// Wire shape of `Activity`. The optional fields stay raw JSON until
+// converted, so one in an unexpected shape is left empty instead of failing
+// the whole transcript
+#[derive(Deserialize)]
+struct RawActivity {
+    sigle: String,
+    #[serde(rename = "titreActivite")]
+    titre: String,
+    note: Option<String>,
+    groupe: u32,
+    #[serde(alias = "nbCredits", default)]
+    credits: Option<Value>,
+    #[serde(alias = "statutActivite", default)]
+    statut: Option<Value>,
+    #[serde(default)]
+    mention: Option<Value>,
+    #[serde(rename = "typeActivite", alias = "type", default)]
+    type_activite: Option<Value>,
+    #[serde(rename = "dateDebut", default)]
+    date_debut: Option<Value>,
+    #[serde(rename = "dateFin", default)]
+    date_fin: Option<Value>,
+    #[serde(flatten)]
+    extra: HashMap<String, Value>,
+}
+
+impl From<RawActivity> for Activity {
+    fn from(raw: RawActivity) -> Self {
+        let mut malformed = Vec::new();
+        let m = &mut malformed;
+
+        Self {
+            credits: lenient("credits", raw.credits, de_opt_number, m),
+            statut: lenient("statut", raw.statut, serde_json::from_value, m),
+            mention: lenient("mention", raw.mention, serde_json::from_value, m),
+            type_activite: lenient("typeActivite", raw.type_activite, serde_json::from_value, m),
+            date_debut: lenient("dateDebut", raw.date_debut, de_opt_date, m),
+            date_fin: lenient("dateFin", raw.date_fin, de_opt_date, m),
+            sigle: raw.sigle,
+            titre: raw.titre,
+            note: raw.note,
+            groupe: raw.groupe,
+            extra: raw.extra,
+            malformed,
+        }
+    }
+}
+
+impl Activity {
+    pub fn is_abandoned(&self) -> bool {
+        matches!(self.statut, Some(ActivityStatus::Abandon))
+    }
+}
+
+/// Outcome of an activity as reported by the transcript.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(from = "String")]
+pub enum ActivityStatus {
+    EnCours,
+    Reussite,
+    Echec,
+    Abandon,
+    Other(String),
+}
+
+impl From<String> for ActivityStatus {
+    fn from(raw: String) -> Self {
+        let normalized = raw.trim().to_lowercase().replace(['é', 'è'], "e");
+
+        match normalized.as_str() {
+            "inscrit" | "en cours" | "encours" => ActivityStatus::EnCours,
+            "reussite" | "reussi" | "succes" => ActivityStatus::Reussite,
+            "echec" => ActivityStatus::Echec,
+            s if s.starts_with("abandon") => ActivityStatus::Abandon,
+            _ => ActivityStatus::Other(raw),
+        }
+    }
+}
+
+impl fmt::Display for ActivityStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ActivityStatus::EnCours => write!(f, "En cours"),
+            ActivityStatus::Reussite => write!(f, "Réussite"),
+            ActivityStatus::Echec => write!(f, "Échec"),
+            ActivityStatus::Abandon => write!(f, "Abandon"),
+            ActivityStatus::Other(raw) => write!(f, "{}", raw),
+        }
+    }
 }
 
 // --- COURSE DETAIL STRUCTS ---
@@ -80,98 +201,477 @@ pub struct DetailProgramme {
 pub struct DetailActivity {
     pub total: Option<f64>,
     pub note: Option<String>,
+    #[serde(default, deserialize_with = "de_evaluations")]
+    pub evaluations: Vec<Evaluation>,
+    /// Group average of the course total, in percent (when published).
+    #[serde(
+        rename = "moyenneGroupe",
+        alias = "moyenne",
+        default,
+        deserialize_with = "de_opt_number"
+    )]
+    pub moyenne_groupe: Option<f64>,
+    #[serde(
+        rename = "ecartTypeGroupe",
+        alias = "ecartType",
+        default,
+        deserialize_with = "de_opt_number"
+    )]
+    pub ecart_type_groupe: Option<f64>,
+}
+
+impl DetailActivity {
+    pub fn stats(&self) -> GroupStats {
+        GroupStats::new(self.total, self.moyenne_groupe, self.ecart_type_groupe)
+    }
+}
+
+/// One graded item of a course (exam, assignment, lab...).
+#[derive(Deserialize, Debug, Clone)]
+pub struct Evaluation {
+    #[serde(alias = "libelle", alias = "titreEvaluation")]
+    pub titre: String,
+    /// Weight of the evaluation in the final grade, in percent.
+    #[serde(default, deserialize_with = "de_opt_number")]
+    pub ponderation: Option<f64>,
+    /// Score obtained, out of `note_maximale`. `None` until posted.
+    #[serde(default, deserialize_with = "de_opt_number")]
+    pub note: Option<f64>,
+    #[serde(
+        rename = "noteMaximale",
+        alias = "sur",
+        default,
+        deserialize_with = "de_opt_number"
+    )]
+    pub note_maximale: Option<f64>,
+    #[serde(rename = "dateDiffusion", alias = "datePublication", default)]
+    pub date_diffusion: Option<String>,
+    /// Group average, on the same scale as `note`.
+    #[serde(default, deserialize_with = "de_opt_number")]
+    pub moyenne: Option<f64>,
+    /// Group standard deviation, on the same scale as `note`.
+    #[serde(rename = "ecartType", default, deserialize_with = "de_opt_number")]
+    pub ecart_type: Option<f64>,
 }
 
-// --- API FUNCTIONS ---
+impl Evaluation {
+    pub fn is_posted(&self) -> bool {
+        self.note.is_some()
+    }
 
-pub async fn get_token(username: &str, password: &str) -> Result<String, String> {
-    let client = Client::new();
-    let payload = json!({ "identifiant": username, "motDePasse": password });
+    /// Score as a percentage of the evaluation (assumes /100 when unspecified).
+    pub fn percentage(&self) -> Option<f64> {
+        let max = self.note_maximale.unwrap_or(100.0);
+        match self.note {
+            Some(note) if max > 0.0 => Some(note / max * 100.0),
+            _ => None,
+        }
+    }
 
-    let response = client
-        .post(AUTH_ENDPOINT)
-        .json(&payload)
-        .send()
-        .await
-        .map_err(|e| format!("Request failed: {}", e))?;
+    /// Points this evaluation adds to the course total, in percent.
+    pub fn contribution(&self) -> Option<f64> {
+        Some(self.percentage()? * self.ponderation? / 100.0)
+    }
 
-    let auth_data = response
-        .json::<AuthResponse>()
-        .await
-        .map_err(|e| format!("Failed to parse auth response: {}", e))?;
+    /// Our score against the group, everything scaled to percent.
+    pub fn stats(&self) -> GroupStats {
+        let scale = 100.0 / self.note_maximale.unwrap_or(100.0);
+        GroupStats::new(
+            self.percentage(),
+            self.moyenne.map(|m| m * scale),
+            self.ecart_type.map(|sd| sd * scale),
+        )
+    }
+}
 
-    if auth_data.token.is_empty() {
-        return Err("Received empty token from server".to_string());
+/// Our score next to the group mean / standard deviation, in percent.
+#[derive(Debug, Clone, Copy)]
+pub struct GroupStats {
+    pub score: Option<f64>,
+    pub mean: Option<f64>,
+    pub std_dev: Option<f64>,
+}
+
+impl GroupStats {
+    fn new(score: Option<f64>, mean: Option<f64>, std_dev: Option<f64>) -> Self {
+        Self {
+            score,
+            mean,
+            std_dev,
+        }
     }
 
-    Ok(auth_data.token)
+    /// Points above (positive) or below the group mean.
+    pub fn delta(&self) -> Option<f64> {
+        Some(self.score? - self.mean?)
+    }
+
+    /// Standard deviations away from the group mean.
+    pub fn z_score(&self) -> Option<f64> {
+        let sd = self.std_dev.filter(|sd| *sd > 0.0)?;
+        Some(self.delta()? / sd)
+    }
 }
 
-pub async fn fetch_transcript(token: &str) -> Result<Vec<SemesterResult>, String> {
-    let client = Client::new();
+// Accepts "2025-09-02" as well as full timestamps ("2025-09-02T00:00:00")
+fn de_opt_date<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<NaiveDate>, D::Error> {
+    let raw = Option::<String>::deserialize(deserializer)?;
+    Ok(raw.and_then(|s| NaiveDate::parse_from_str(s.get(..10)?, "%Y-%m-%d").ok()))
+}
 
-    let response = client
-        .get(RESUME_ENDPOINT)
-        .bearer_auth(token)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to fetch transcript: {}", e))?;
+// Converts one optional field, leaving it empty and noting its name in
+// `malformed` when the value has an unexpected shape
+fn lenient<T>(
+    name: &'static str,
+    value: Option<Value>,
+    parse: fn(Value) -> Result<Option<T>, serde_json::Error>,
+    malformed: &mut Vec<&'static str>,
+) -> Option<T> {
+    match parse(value?) {
+        Ok(parsed) => parsed,
+        Err(_) => {
+            malformed.push(name);
+            None
+        }
+    }
+}
 
-    let mut resume: ResumeResponse = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse transcript: {}", e))?;
+// Item by item, so one evaluation in an unexpected shape is skipped instead
+// of failing the whole course detail
+fn de_evaluations<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Evaluation>, D::Error> {
+    let raw = Option::<Vec<Value>>::deserialize(deserializer)?;
+    Ok(raw
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|item| serde_json::from_value(item).ok())
+        .collect())
+}
 
-    // Sort descending (Newest first)
-    resume
-        .data
-        .resultats
-        .sort_by(|a, b| b.trimestre.cmp(&a.trimestre));
+// The portal sometimes sends numbers as French-formatted strings ("24,5")
+fn de_opt_number<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f64>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Number(f64),
+        Text(String),
+    }
 
-    Ok(resume.data.resultats)
+    Ok(match Option::<Raw>::deserialize(deserializer)? {
+        Some(Raw::Number(n)) => Some(n),
+        Some(Raw::Text(t)) => t.trim().replace(',', ".").parse().ok(),
+        None => None,
+    })
 }
 
-pub async fn fetch_course_details(
-    token: &str,
-    semester: u32,
-    sigle: &str,
-    group: u32,
-) -> Result<DetailActivity, String> {
-    let client = Client::new();
-    let url = format!("{}/{}/{}/{}", DETAIL_ENDPOINT, semester, sigle, group);
-
-    let response = client
-        .get(&url)
-        .bearer_auth(token)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to fetch course {}: {}", sigle, e))?;
-
-    let details: CourseDetailResponse = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse course {}: {}", sigle, e))?;
-
-    if let Some(res) = details.data.resultats.first() {
-        if let Some(prog) = res.programmes.first() {
-            if let Some(act) = prog.activites.first() {
-                return Ok(DetailActivity {
-                    total: act.total,
-                    note: act.note.clone(),
-                });
+// --- API CLIENT ---
+
+/// Owns a single pooled HTTP client and the bearer token for a session.
+/// Reuse one instance for a whole run so TLS connections are kept alive.
+pub struct ApiClient {
+    http: Client,
+    base_url: String,
+    // Kept after `login` so an expired token can be renewed transparently
+    credentials: Option<(String, String)>,
+    token: RwLock<Option<String>>,
+    retry: RetryPolicy,
+    // Max course detail requests in flight at once
+    concurrency: usize,
+    log: Log,
+}
+
+impl ApiClient {
+    pub fn new(base_url: &str, timeout: Duration, retry: RetryPolicy) -> Result<Self, UqgrdError> {
+        let http = Client::builder()
+            .user_agent(USER_AGENT)
+            .timeout(timeout)
+            .connect_timeout(Duration::from_secs(DEFAULT_CONNECT_TIMEOUT_SECS))
+            .build()
+            .map_err(|e| UqgrdError::Config(format!("Failed to build HTTP client: {}", e)))?;
+
+        Ok(Self {
+            http,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            credentials: None,
+            token: RwLock::new(None),
+            retry,
+            concurrency: DEFAULT_MAX_CONCURRENCY,
+            log: Log::default(),
+        })
+    }
+
+    /// Base URL priority: `UQGRD_BASE_URL` env > `base_url` in config.json > monportail.
+    pub fn from_env() -> Result<Self, UqgrdError> {
+        let config_url = auth::load_config().ok().and_then(|c| c.base_url);
+        let base_url = env::var("UQGRD_BASE_URL")
+            .ok()
+            .or(config_url)
+            .unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
+        let timeout = common::env_or("REQUEST_TIMEOUT", DEFAULT_REQUEST_TIMEOUT_SECS);
+
+        let client = Self::new(
+            &base_url,
+            Duration::from_secs(timeout),
+            RetryPolicy::from_env(),
+        )?;
+
+        Ok(client.with_concurrency(common::env_or("MAX_CONCURRENCY", DEFAULT_MAX_CONCURRENCY)))
+    }
+
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Sends retry and re-login messages to `log` instead of stderr.
+    pub fn with_log(mut self, log: Log) -> Self {
+        self.log = log;
+        self
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    async fn token(&self) -> Result<String, UqgrdError> {
+        self.token
+            .read()
+            .await
+            .clone()
+            .ok_or(UqgrdError::TokenExpired)
+    }
+
+    pub async fn login(&mut self, username: &str, password: &str) -> Result<(), UqgrdError> {
+        let token = self.request_token(username, password).await?;
+
+        self.credentials = Some((username.to_string(), password.to_string()));
+        *self.token.write().await = Some(token);
+        Ok(())
+    }
+
+    /// Logs in again with the stored credentials, unless another request
+    /// already replaced the `stale` token in the meantime.
+    async fn reauthenticate(&self, stale: &str) -> Result<(), UqgrdError> {
+        let (username, password) = self.credentials.as_ref().ok_or(UqgrdError::TokenExpired)?;
+
+        // Holding the write lock makes concurrent 401s share one login
+        let mut token = self.token.write().await;
+        if token.as_deref() != Some(stale) {
+            return Ok(());
+        }
+
+        self.log
+            .emit("   🔑 Session expired, logging in again...".to_string());
+        *token = Some(self.request_token(username, password).await?);
+        Ok(())
+    }
+
+    async fn request_token(&self, username: &str, password: &str) -> Result<String, UqgrdError> {
+        self.retry
+            .run("Login", &self.log, || self.send_login(username, password))
+            .await
+    }
+
+    async fn send_login(&self, username: &str, password: &str) -> Result<String, UqgrdError> {
+        let payload = json!({ "identifiant": username, "motDePasse": password });
+
+        let response = self
+            .http
+            .post(self.url(AUTH_PATH))
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| UqgrdError::Network(format!("Login request failed: {}", e)))?;
+
+        // A refused login is a credentials problem, not an expired session
+        let status = response.status();
+        if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
+            return Err(UqgrdError::AuthRejected);
+        }
+        if !status.is_success() {
+            return Err(UqgrdError::Http {
+                status: status.as_u16(),
+                url: response.url().to_string(),
+            });
+        }
+
+        let auth_data = response
+            .json::<AuthResponse>()
+            .await
+            .map_err(|e| UqgrdError::Parse(format!("auth response: {}", e)))?;
+
+        if auth_data.token.is_empty() {
+            return Err(UqgrdError::AuthRejected);
+        }
+
+        Ok(auth_data.token)
+    }
+
+    /// Authenticated GET decoded as JSON. `context` names the resource in errors.
+    /// Transient failures follow the retry policy; a refused token triggers one
+    /// re-login and a single replay of the request.
+    async fn get_json<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        context: &str,
+    ) -> Result<T, UqgrdError> {
+        let token = self.token().await?;
+
+        let result = self
+            .retry
+            .run(context, &self.log, || self.send_get(url, &token, context))
+            .await;
+
+        match result {
+            Err(e) if e.needs_reauth() && self.credentials.is_some() => {
+                self.reauthenticate(&token).await?;
+                let token = self.token().await?;
+                self.retry
+                    .run(context, &self.log, || self.send_get(url, &token, context))
+                    .await
             }
+            result => result,
         }
     }
 
-    Err(format!("Details not found in response for {}", sigle))
+    async fn send_get<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        token: &str,
+        context: &str,
+    ) -> Result<T, UqgrdError> {
+        let response = self
+            .http
+            .get(url)
+            .bearer_auth(token)
+            .send()
+            .await
+            .map_err(|e| UqgrdError::Network(format!("{}: {}", context, e)))?;
+
+        let status = response.status();
+        if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
+            return Err(UqgrdError::TokenExpired);
+        }
+        if !status.is_success() {
+            return Err(UqgrdError::Http {
+                status: status.as_u16(),
+                url: url.to_string(),
+            });
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| UqgrdError::Parse(format!("{}: {}", context, e)))
+    }
+
+    pub async fn fetch_transcript(&self) -> Result<Vec<SemesterResult>, UqgrdError> {
+        let mut resume: ResumeResponse =
+            self.get_json(&self.url(RESUME_PATH), "transcript").await?;
+
+        // Sort descending (Newest first)
+        resume.data.resultats.sort_by_key(|s| Reverse(s.trimestre));
+
+        report_unknown_fields(&resume.data.resultats, &self.log);
+
+        Ok(resume.data.resultats)
+    }
+
+    pub async fn fetch_course_details(
+        &self,
+        semester: u32,
+        sigle: &str,
+        group: u32,
+    ) -> Result<DetailActivity, UqgrdError> {
+        let url = self.url(&format!("{}/{}/{}/{}", DETAIL_PATH, semester, sigle, group));
+        let details: CourseDetailResponse =
+            self.get_json(&url, &format!("course {}", sigle)).await?;
+
+        details
+            .data
+            .resultats
+            .into_iter()
+            .next()
+            .and_then(|res| res.programmes.into_iter().next())
+            .and_then(|prog| prog.activites.into_iter().next())
+            .ok_or_else(|| UqgrdError::Parse(format!("no details in response for {}", sigle)))
+    }
+
+    /// Fetches the details of every activity with at most `concurrency`
+    /// requests in flight. Results are returned in the order of `activities`.
+    pub async fn fetch_all_course_details(
+        &self,
+        semester: u32,
+        activities: &[Activity],
+    ) -> Vec<Result<DetailActivity, UqgrdError>> {
+        stream::iter(activities)
+            .map(|activity| self.fetch_course_details(semester, &activity.sigle, activity.groupe))
+            .buffered(self.concurrency)
+            .collect()
+            .await
+    }
+}
+
+// --- TRANSCRIPT HELPERS ---
+
+// Set once the unknown fields have been logged, so a daemon or a refreshing
+// TUI doesn't repeat the same line on every fetch
+static UNKNOWN_FIELDS_REPORTED: AtomicBool = AtomicBool::new(false);
+
+// Schema drift shows up here first: log fields the structs don't capture
+fn report_unknown_fields(transcript: &[SemesterResult], log: &Log) {
+    if UNKNOWN_FIELDS_REPORTED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let mut unknown = BTreeSet::new();
+
+    for prog in transcript.iter().flat_map(|s| &s.programmes) {
+        unknown.extend(prog.extra.keys().map(|k| format!("programme.{}", k)));
+        for activity in &prog.activites {
+            unknown.extend(activity.extra.keys().map(|k| format!("activite.{}", k)));
+            unknown.extend(
+                activity
+                    .malformed
+                    .iter()
+                    .map(|k| format!("activite.{} (unexpected value)", k)),
+            );
+        }
+    }
+
+    if !unknown.is_empty() && !UNKNOWN_FIELDS_REPORTED.swap(true, Ordering::Relaxed) {
+        let fields: Vec<_> = unknown.into_iter().collect();
+        log.emit(format!(
+            "   ℹ️  Unrecognized transcript fields: {}",
+            fields.join(", ")
+        ));
+    }
+}
+
+/// Finds the most recent enrollment in `sigle` (transcript is sorted newest first).
+pub fn find_course<'a>(
+    transcript: &'a [SemesterResult],
+    sigle: &str,
+) -> Option<(&'a SemesterResult, &'a Programme, &'a Activity)> {
+    transcript.iter().find_map(|sem| {
+        sem.programmes.iter().find_map(|prog| {
+            prog.activites
+                .iter()
+                .find(|act| act.sigle.eq_ignore_ascii_case(sigle))
+                .map(|act| (sem, prog, act))
+        })
+    })
 }
 
 // --- DATE & SEMESTER LOGIC ---
 
 pub fn get_current_semester_code() -> u32 {
-    let now = Local::now();
-    let year = now.year();
-    let month = now.month();
+    semester_code_at(Local::now())
+}
+
+/// Semester in session on the given date.
+pub fn semester_code_at(date: impl Datelike) -> u32 {
+    let year = date.year();
+    let month = date.month();
 
     match month {
         1..=4 => (year * 10 + 1) as u32, // Hiver (Jan-Apr)
@@ -180,6 +680,47 @@ pub fn get_current_semester_code() -> u32 {
     }
 }
 
+/// Parses a semester given as the raw portal code (`20253`) or in human form:
+/// `A2025`, `Automne 2025`, `H26`, `Été 2025`, `2025A`...
+pub fn parse_semester_code(input: &str) -> Option<u32> {
+    let normalized: String = input
+        .trim()
+        .to_lowercase()
+        .replace(['é', 'è'], "e")
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect();
+
+    if normalized.len() == 5 && normalized.chars().all(|c| c.is_ascii_digit()) {
+        let code: u32 = normalized.parse().ok()?;
+        return (1..=3).contains(&(code % 10)).then_some(code);
+    }
+
+    // Split into the term word and the year, in either order
+    let (term, year) = match normalized.find(|c: char| c.is_ascii_digit())? {
+        0 => {
+            let split = normalized.find(|c: char| c.is_alphabetic())?;
+            (&normalized[split..], &normalized[..split])
+        }
+        split => (&normalized[..split], &normalized[split..]),
+    };
+
+    let term_digit = match term {
+        "h" | "hiver" | "w" | "winter" => 1,
+        "e" | "ete" | "s" | "summer" => 2,
+        "a" | "automne" | "f" | "fall" => 3,
+        _ => return None,
+    };
+
+    let year: u32 = match year.len() {
+        2 => 2000 + year.parse::<u32>().ok()?,
+        4 => year.parse().ok()?,
+        _ => return None,
+    };
+
+    Some(year * 10 + term_digit)
+}
+
 pub fn format_semester_name(code: u32) -> String {
     let s = code.to_string();
     if s.len() != 5 {
@@ -1,4 +1,5 @@
 use crate::constants::APP_NAME;
+use crate::modules::error::UqgrdError;
 use directories::ProjectDirs;
 use keyring::Entry;
 use serde::{Deserialize, Serialize};
@@ -10,6 +11,9 @@ pub struct Config {
     pub username: String,
     // Use Option to handle both cases (Some = text file, None = keyring)
     pub password: Option<String>,
+    // Optional override of the portal URL (e.g. a local stand-in for testing)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
 }
 
 // Updated signature to accept the flag
@@ -17,9 +21,9 @@ pub fn save_credentials(
     username: &str,
     password: &str,
     skip_encryption: bool,
-) -> Result<(), String> {
+) -> Result<(), UqgrdError> {
     let config_dir = get_config_dir()?;
-    fs::create_dir_all(&config_dir).map_err(|e| e.to_string())?;
+    fs::create_dir_all(&config_dir).map_err(|e| UqgrdError::Io(e.to_string()))?;
     let config_path = config_dir.join("config.json");
 
     // LOGIC: If skipping encryption, save password in struct. Otherwise, keep it None.
@@ -30,49 +34,62 @@ pub fn save_credentials(
         } else {
             None
         },
+        // Keep a previously configured portal override
+        base_url: load_config().ok().and_then(|c| c.base_url),
     };
 
-    let json = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
-    fs::write(&config_path, json).map_err(|e| e.to_string())?;
+    let json =
+        serde_json::to_string_pretty(&config).map_err(|e| UqgrdError::Config(e.to_string()))?;
+    fs::write(&config_path, json).map_err(|e| UqgrdError::Io(e.to_string()))?;
 
     if skip_encryption {
         println!("⚠️  WARNING: Password saved in plain text (Insecure Mode)");
     } else {
         // Only try to use the OS keyring if the user didn't skip it
-        let entry = Entry::new(APP_NAME, username).map_err(|e| e.to_string())?;
-        entry.set_password(password).map_err(|e| e.to_string())?;
+        let entry =
+            Entry::new(APP_NAME, username).map_err(|e| UqgrdError::Keyring(e.to_string()))?;
+        entry
+            .set_password(password)
+            .map_err(|e| UqgrdError::Keyring(e.to_string()))?;
         println!("🔒 Password securely saved in OS Keyring");
     }
 
     Ok(())
 }
 
-pub fn get_credentials() -> Result<(String, String), String> {
+pub fn load_config() -> Result<Config, UqgrdError> {
     let config_dir = get_config_dir()?;
     let config_path = config_dir.join("config.json");
 
-    let file_content = fs::read_to_string(&config_path)
-        .map_err(|_| format!("No config found. Run 'uqgrd credentials' first."))?;
+    let file_content = fs::read_to_string(&config_path).map_err(|_| {
+        UqgrdError::Config("No config found. Run 'uqgrd credentials' first.".into())
+    })?;
 
-    let config: Config = serde_json::from_str(&file_content)
-        .map_err(|e| format!("Failed to parse config: {}", e))?;
+    serde_json::from_str(&file_content)
+        .map_err(|e| UqgrdError::Config(format!("Failed to parse config: {}", e)))
+}
+
+pub fn get_credentials() -> Result<(String, String), UqgrdError> {
+    let config = load_config()?;
 
     // LOGIC: Check config file first. If None, check Keyring.
     let password = match config.password {
         Some(p) => p, // Found in file (Insecure mode)
         None => {
             // Not in file, check Keyring (Secure mode)
-            let entry = Entry::new(APP_NAME, &config.username).map_err(|e| e.to_string())?;
+            let entry = Entry::new(APP_NAME, &config.username)
+                .map_err(|e| UqgrdError::Keyring(e.to_string()))?;
             entry
                 .get_password()
-                .map_err(|e| format!("Keyring Error for user '{}': {}", config.username, e))?
+                .map_err(|e| UqgrdError::Keyring(format!("user '{}': {}", config.username, e)))?
         }
     };
 
     Ok((config.username, password))
 }
 
-pub fn get_config_dir() -> Result<PathBuf, String> {
-    let dirs = ProjectDirs::from("", "", APP_NAME).ok_or("Could not determine home directory")?;
+pub fn get_config_dir() -> Result<PathBuf, UqgrdError> {
+    let dirs = ProjectDirs::from("", "", APP_NAME)
+        .ok_or_else(|| UqgrdError::Config("Could not determine home directory".into()))?;
     Ok(dirs.config_dir().to_path_buf())
 }
@@ -0,0 +1,76 @@
+use crate::modules::error::UqgrdError;
+use chrono::Local;
+use std::env;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+/// Reads an env var and parses it, falling back to `default` when missing or invalid.
+pub fn env_or<T: FromStr>(key: &str, default: T) -> T {
+    env::var(key)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(default)
+}
+
+/// Where progress messages of the API client go: stderr by default, or a
+/// buffer for full-screen views that can't have the terminal written over.
+#[derive(Clone, Default)]
+pub struct Log {
+    buffer: Option<Arc<Mutex<Vec<String>>>>,
+}
+
+impl Log {
+    pub fn buffered() -> Self {
+        Self {
+            buffer: Some(Arc::default()),
+        }
+    }
+
+    pub fn emit(&self, message: String) {
+        match &self.buffer {
+            Some(buffer) => buffer
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push(message.trim().to_string()),
+            None => eprintln!("{}", message),
+        }
+    }
+
+    /// Buffered messages since the last call, oldest first.
+    pub fn take(&self) -> Vec<String> {
+        match &self.buffer {
+            Some(buffer) => std::mem::take(&mut *buffer.lock().unwrap_or_else(|e| e.into_inner())),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Writes to a temporary file next to `path` and renames it into place, so a
+/// crash mid-write leaves the previous contents intact.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), UqgrdError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| UqgrdError::Io(e.to_string()))?;
+    }
+    let mut tmp_name = path.as_os_str().to_owned();
+    tmp_name.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_name);
+
+    let mut file = File::create(&tmp_path).map_err(|e| UqgrdError::Io(e.to_string()))?;
+    file.write_all(contents)
+        .and_then(|_| file.sync_all())
+        .map_err(|e| UqgrdError::Io(e.to_string()))?;
+    fs::rename(&tmp_path, path).map_err(|e| UqgrdError::Io(e.to_string()))
+}
+
+/// Renames an unreadable file to `<name>.corrupt-<timestamp>` and returns the new path.
+pub fn move_aside(path: &Path) -> Result<PathBuf, UqgrdError> {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(format!(".corrupt-{}", Local::now().format("%Y%m%d-%H%M%S")));
+    let backup = PathBuf::from(backup);
+
+    fs::rename(path, &backup).map_err(|e| UqgrdError::Io(e.to_string()))?;
+    Ok(backup)
+}
@@ -1,134 +1,545 @@
-use crate::constants::DEFAULT_CHECK_INTERVAL_MINUTES;
-use crate::constants::STATE_FILE;
-use crate::modules::api::{self, DetailActivity};
+use crate::constants::DEFAULT_START_DELAY_SECS;
+use crate::constants::{EXIT_CHANGES_DEFERRED, EXIT_CHANGES_NOTIFIED, EXIT_PARTIAL_FAILURE};
+use crate::modules::api::{self, Activity, ApiClient, DetailActivity, Evaluation};
 use crate::modules::auth;
-use chrono::Local;
+use crate::modules::common;
+use crate::modules::display;
+use crate::modules::error::UqgrdError;
+use crate::modules::history::{self, HistoryEntry};
+use crate::modules::outbox::Outbox;
+use crate::modules::schedule::{self, QuietHours, Schedule};
+use crate::modules::state::{
+    clear_state, load_or_recover_state, load_state, save_state, CourseKey, CourseState, DirLock,
+    GradesState, STATE_VERSION,
+};
+use chrono::{Local, Utc};
 use lettre::transport::smtp::authentication::Credentials;
 use lettre::{Message, SmtpTransport, Transport};
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use std::env;
-use std::fs;
-use std::thread;
 use std::time::Duration;
-
-// --- STATE MANAGEMENT ---
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct CourseState {
-    total: Option<f64>,
-    note: Option<String>,
+use tokio::sync::watch;
+
+/// Outcome of one check cycle, used by `uqgrd check` to pick its exit code.
+#[derive(Debug, Default)]
+pub struct CheckReport {
+    /// Notifications delivered, including ones queued by earlier cycles.
+    pub notified: usize,
+    /// Courses that could not be fetched and failed delivery attempts.
+    pub failures: usize,
+    /// Notifications left in the outbox without an attempt this cycle, held
+    /// back by quiet hours or waiting for their retry delay.
+    pub deferred: usize,
 }
 
-type GradesState = HashMap<String, CourseState>;
+impl CheckReport {
+    /// 0 when nothing changed, 20 when changes were notified, 21 when some
+    /// courses failed (takes precedence, so the failure is not hidden), 22
+    /// when changes are queued but not sent yet.
+    pub fn exit_code(&self) -> i32 {
+        if self.failures > 0 {
+            EXIT_PARTIAL_FAILURE
+        } else if self.notified > 0 {
+            EXIT_CHANGES_NOTIFIED
+        } else if self.deferred > 0 {
+            EXIT_CHANGES_DEFERRED
+        } else {
+            0
+        }
+    }
+}
 
-pub async fn start_daemon() {
-    // 1. Load Interval from Env (Default: 60 min)
-    let interval_min: u64 = env::var("CHECK_INTERVAL")
-        .ok() // Convert Result to Option
-        .and_then(|v| v.parse().ok()) // Parse string to u64, return None if fails
-        .unwrap_or(DEFAULT_CHECK_INTERVAL_MINUTES); // Fallback to constant
+pub async fn start_daemon() -> Result<(), UqgrdError> {
+    // 1. Load Schedule from Env (Default: every CHECK_INTERVAL minutes)
+    let mut schedule = Schedule::from_env()?;
+    let quiet = QuietHours::from_env()?;
+    let start_delay: u64 = common::env_or("START_DELAY", DEFAULT_START_DELAY_SECS);
+    let _instance = DirLock::daemon()?;
 
     println!("🚀 Starting UQGRD Daemon...");
-    println!("   Interval: Every {} minutes", interval_min);
+    println!("   Schedule: {}", schedule.describe());
+    if let Some(q) = &quiet {
+        println!("   Quiet hours: {} (notifications deferred)", q.describe());
+    }
+
+    let mut shutdown = shutdown_listener();
+
+    if start_delay > 0 {
+        println!("   Waiting {}s before the first check", start_delay);
+        if !pause(Duration::from_secs(start_delay), &mut shutdown).await {
+            println!("👋 Daemon stopped.");
+            return Ok(());
+        }
+    }
+
+    // Last error we emailed about, so a persistent failure alerts only once
+    let mut last_alert: Option<String> = None;
 
     loop {
+        // Never interrupted: a signal received mid-cycle is handled once the
+        // state has been written
+        run_cycle(&mut last_alert).await;
+
+        // Read under the state lock, like every other access to the state
+        let (state, outbox) = match DirLock::state().await {
+            Ok(_lock) => (load_state().ok(), Outbox::load().ok()),
+            Err(_) => (None, None),
+        };
+        if let Some(state) = state {
+            schedule.observe(&state.change_timestamps());
+        }
+
+        let now = Local::now();
+        let mut next = schedule.next_after(now);
+        // Deferred notifications go out as soon as quiet hours end
+        if let Some(q) = quiet.filter(|q| q.contains(now)) {
+            next = next.min(q.end_after(now));
+        } else if let Some(retry) = outbox
+            .and_then(|o| o.next_retry())
+            .map(|r| r.with_timezone(&Local))
+            .filter(|r| *r > now)
+        {
+            // Failed notifications are retried without waiting for a slow schedule
+            next = next.min(retry);
+        }
+        let wait = (next - now).to_std().unwrap_or_default() + schedule::jitter();
+        if let Some(reason) = schedule.explain(now) {
+            println!("   {}", reason);
+        }
         println!(
-            "Checking grades at {}",
-            Local::now().format("%Y-%m-%d %H:%M:%S")
+            "   Next check at {}",
+            (now + chrono::Duration::from_std(wait).unwrap_or_default())
+                .format("%Y-%m-%d %H:%M:%S")
         );
 
-        if let Err(e) = check_and_notify().await {
+        if !pause(wait, &mut shutdown).await {
+            break;
+        }
+    }
+
+    println!("👋 Daemon stopped.");
+    Ok(())
+}
+
+async fn run_cycle(last_alert: &mut Option<String>) {
+    println!(
+        "Checking grades at {}",
+        Local::now().format("%Y-%m-%d %H:%M:%S")
+    );
+
+    match check_and_notify().await {
+        Ok(_) => *last_alert = None,
+        Err(e) if e.is_transient() => {
+            eprintln!("⚠️  Transient error, will retry next cycle: {}", e);
+        }
+        Err(e) => {
             eprintln!("❌ Error during check cycle: {}", e);
+
+            let message = e.to_string();
+            if e.needs_attention() && last_alert.as_deref() != Some(message.as_str()) {
+                match send_error_alert(&e).await {
+                    Ok(()) => println!("   📧 Error alert sent."),
+                    Err(mail_err) => eprintln!("   Failed to send error alert: {}", mail_err),
+                }
+                *last_alert = Some(message);
+            }
         }
+    }
+}
 
-        // Sleep using the dynamic variable
-        thread::sleep(Duration::from_secs(interval_min * 60));
+// Sleeps for `duration`; returns false if a shutdown was requested meanwhile
+async fn pause(duration: Duration, shutdown: &mut watch::Receiver<bool>) -> bool {
+    tokio::select! {
+        _ = tokio::time::sleep(duration) => true,
+        _ = shutdown.wait_for(|stop| *stop) => false,
     }
 }
 
-async fn check_and_notify() -> Result<(), String> {
+// Flips to true on the first SIGTERM/SIGINT; a second one exits immediately
+fn shutdown_listener() -> watch::Receiver<bool> {
+    let (tx, rx) = watch::channel(false);
+
+    tokio::spawn(async move {
+        let mut signals = match Signals::new() {
+            Ok(signals) => signals,
+            Err(e) => {
+                eprintln!("⚠️  Could not install signal handlers: {}", e);
+                return;
+            }
+        };
+
+        signals.recv().await;
+        println!("🛑 Shutdown requested, stopping after the current cycle...");
+        let _ = tx.send(true);
+
+        signals.recv().await;
+        eprintln!("🛑 Second signal received, exiting now.");
+        std::process::exit(130);
+    });
+
+    rx
+}
+
+#[cfg(unix)]
+struct Signals {
+    term: tokio::signal::unix::Signal,
+    int: tokio::signal::unix::Signal,
+}
+
+#[cfg(unix)]
+impl Signals {
+    fn new() -> std::io::Result<Self> {
+        use tokio::signal::unix::{signal, SignalKind};
+        Ok(Self {
+            term: signal(SignalKind::terminate())?,
+            int: signal(SignalKind::interrupt())?,
+        })
+    }
+
+    async fn recv(&mut self) {
+        tokio::select! {
+            _ = self.term.recv() => {}
+            _ = self.int.recv() => {}
+        }
+    }
+}
+
+#[cfg(not(unix))]
+struct Signals;
+
+#[cfg(not(unix))]
+impl Signals {
+    fn new() -> std::io::Result<Self> {
+        Ok(Self)
+    }
+
+    async fn recv(&mut self) {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+/// What to do on the first check, when there is no saved state yet (`FIRST_RUN`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FirstRun {
+    /// Record the current grades without notifying.
+    Silent,
+    /// Record them and send a single email listing every course.
+    Summary,
+    /// Treat every graded course as new and email each one.
+    Notify,
+}
+
+impl FirstRun {
+    pub fn from_env() -> Result<Self, UqgrdError> {
+        match env::var("FIRST_RUN")
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase()
+            .as_str()
+        {
+            "" | "silent" => Ok(FirstRun::Silent),
+            "summary" => Ok(FirstRun::Summary),
+            "notify" => Ok(FirstRun::Notify),
+            other => Err(UqgrdError::Config(format!(
+                "Invalid FIRST_RUN '{}' (use silent, summary or notify)",
+                other
+            ))),
+        }
+    }
+
+    /// Whether a check on `state` records a baseline instead of notifying.
+    pub fn records_baseline(self, state: &GradesState) -> bool {
+        state.fresh && self != FirstRun::Notify
+    }
+}
+
+type CourseResults = Vec<(Activity, Result<DetailActivity, UqgrdError>)>;
+
+/// Runs a single fetch/compare/notify cycle over the current semester.
+pub async fn check_and_notify() -> Result<CheckReport, UqgrdError> {
     // 1. Load Credentials (API) - This will fail if not configured on the server
     let (username, password) = auth::get_credentials()?;
+    let first_run = FirstRun::from_env()?;
+    let quiet = QuietHours::from_env()?.is_some_and(|q| q.contains(Local::now()));
+    let mut report = CheckReport::default();
+
+    // 2. Authenticate
+    let mut client = ApiClient::from_env()?;
+    client.login(&username, &password).await?;
+
+    // 3. Fetch Current Semester (details concurrently, bounded by MAX_CONCURRENCY).
+    // The state lock is not held yet, so `diff` or `tui` never wait on the portal
+    let current = fetch_current_courses(&client).await?;
+
+    // 4. Compare with the saved state and queue alerts, under the state lock
+    {
+        let _lock = DirLock::state().await?;
+        let state = load_or_recover_state()?;
+        if state.migrated {
+            println!(
+                "🔄 Migrated {} course(s) to state format v{}",
+                state.len(),
+                STATE_VERSION
+            );
+        }
+        match &current {
+            Some((trimestre, courses)) => {
+                record_changes(state, *trimestre, courses, first_run, &mut report)?
+            }
+            // Nothing was recorded: a fresh state stays fresh for the next cycle
+            None if state.migrated => save_state(&state)?,
+            None => {}
+        }
+    }
 
-    // 2. Load Saved State (Previous Grades)
-    let mut state = load_state()?;
-    let mut state_changed = false;
+    // 5. Deliver everything due, including leftovers from earlier cycles
+    deliver(&username, quiet, &mut report).await?;
 
-    // 3. Authenticate
-    let token = api::get_token(&username, &password).await?;
+    Ok(report)
+}
 
-    // 4. Fetch Current Semester
-    let current_sem_code = api::get_current_semester_code();
-    let transcript = api::fetch_transcript(&token).await?;
-
-    // Find the current semester
-    if let Some(sem) = transcript.iter().find(|s| s.trimestre == current_sem_code) {
-        for prog in &sem.programmes {
-            for activity in &prog.activites {
-                // Fetch live details
-                let details = api::fetch_course_details(
-                    &token,
-                    sem.trimestre,
-                    &activity.sigle,
-                    activity.groupe,
-                )
-                .await;
-
-                match details {
-                    Ok(new_data) => {
-                        // Check if grade changed
-                        if has_grade_changed(&state, &activity.sigle, &new_data) {
-                            println!(
-                                "🔔 CHANGE DETECTED: {} ({})",
-                                activity.sigle, activity.titre
-                            );
-
-                            // Send Alert
-                            if let Err(e) = send_email_alert(
-                                &username,
-                                &activity.sigle,
-                                &activity.titre,
-                                &new_data,
-                            ) {
-                                eprintln!("   Failed to send email: {}", e);
-                            } else {
-                                println!("   📧 Email sent successfully!");
-                            }
-
-                            // Update State
-                            state.insert(
-                                activity.sigle.clone(),
-                                CourseState {
-                                    total: new_data.total,
-                                    note: new_data.note,
-                                },
-                            );
-                            state_changed = true;
-                        }
-                    }
-                    Err(e) => eprintln!("   Failed to fetch details for {}: {}", activity.sigle, e),
+// Compares the fetched courses with the saved state, queues an alert for each
+// change and saves the outbox, history and state. Called with the state lock held
+fn record_changes(
+    mut state: GradesState,
+    trimestre: u32,
+    courses: &CourseResults,
+    first_run: FirstRun,
+    report: &mut CheckReport,
+) -> Result<(), UqgrdError> {
+    let mut outbox = Outbox::load()?;
+    let baseline = first_run.records_baseline(&state);
+    // A fresh state is written once a current semester was fetched, so the
+    // baseline only happens once; courses that failed get a placeholder
+    let mut state_changed = state.migrated || state.fresh;
+    let mut changes = Vec::new();
+
+    if baseline {
+        println!("📌 First run: recording current grades as the baseline");
+    }
+
+    let mut recorded = 0;
+    for (activity, details) in courses {
+        let key = CourseKey::new(trimestre, activity);
+        let new_data = match details {
+            Ok(new_data) => new_data,
+            Err(e) => {
+                eprintln!("   Failed to fetch details for {}: {}", activity.sigle, e);
+                report.failures += 1;
+                if baseline && state.get(&key).is_none() {
+                    state.insert(key, CourseState::unfetched(None));
                 }
+                continue;
+            }
+        };
+
+        state_changed |= state.adopt(&key);
+
+        // First run, or a course the baseline could not fetch back then
+        if baseline || state.awaits_baseline(&key) {
+            if new_data.total.is_some() || new_data.note.is_some() {
+                let posted = new_evaluations(&state, &key, new_data);
+                changes.push(HistoryEntry::new(key.clone(), new_data, &posted));
+            }
+            if !baseline {
+                println!("   📌 {}: recorded without notifying", activity.sigle);
             }
+            let course = CourseState::recorded(state.get(&key), new_data);
+            state.insert(key, course);
+            state_changed = true;
+            recorded += 1;
+            continue;
+        }
+
+        // Check if grade changed
+        if has_grade_changed(&state, &key, new_data) {
+            println!(
+                "🔔 CHANGE DETECTED: {} ({})",
+                activity.sigle, activity.titre
+            );
+
+            let new_evals = new_evaluations(&state, &key, new_data);
+            for eval in &new_evals {
+                println!("   📝 {}", display::describe_evaluation(eval));
+            }
+
+            // Queue the alert; it is sent below, or on a later cycle if
+            // delivery fails or quiet hours are on
+            let (subject, body) =
+                alert_message(&activity.sigle, &activity.titre, new_data, &new_evals);
+            if outbox.enqueue(change_key(&state, &key, new_data), subject, body) {
+                println!("   📬 Notification queued");
+            }
+
+            // Update State
+            changes.push(HistoryEntry::new(key.clone(), new_data, &new_evals));
+            let updated = CourseState::changed(state.get(&key), new_data);
+            state.insert(key, updated);
+            state_changed = true;
         }
-    } else {
-        println!(
-            "   No active semester found for code {} (Are you registered?)",
-            current_sem_code
-        );
     }
 
-    // 5. Save new state
+    if baseline {
+        println!("   Recorded {} course(s) without notifying", recorded);
+        if first_run == FirstRun::Summary {
+            let (subject, body) = baseline_summary_message(trimestre, courses);
+            let key = format!("baseline:{}:{}", trimestre, Local::now().format("%Y-%m-%d"));
+            outbox.enqueue(key, subject, body);
+        }
+    }
+
+    // Persist the queued notifications before the state that marks them
+    // handled, then record history and save the new state
+    outbox.save()?;
+    if let Err(e) = history::append(&changes) {
+        eprintln!("   Failed to record grade history: {}", e);
+    }
     if state_changed {
         save_state(&state)?;
     }
+    Ok(())
+}
+
+// Sends due notifications; each one leaves the outbox only once accepted.
+// They are claimed and recorded under the state lock, but sent without it
+async fn deliver(username: &str, quiet: bool, report: &mut CheckReport) -> Result<(), UqgrdError> {
+    let now = Utc::now();
+    let due = {
+        let _lock = DirLock::state().await?;
+        let mut outbox = Outbox::load()?;
+        outbox.prune(now);
+
+        if quiet {
+            if !outbox.pending.is_empty() {
+                println!(
+                    "   🌙 Quiet hours: {} notification(s) deferred",
+                    outbox.pending.len()
+                );
+            }
+            report.deferred += outbox.pending.len();
+            return outbox.save();
+        }
+
+        let due = outbox.claim_due(now);
+        // Waiting for their retry delay
+        let waiting = outbox.pending.len() - due.len();
+        if waiting > 0 {
+            println!("   📬 {} notification(s) waiting to be retried", waiting);
+        }
+        report.deferred += waiting;
+        outbox.save()?;
+        due
+    };
+
+    for notification in due {
+        let result = send_email(username, &notification.subject, notification.body.clone()).await;
+
+        // Recorded after every attempt so a crash never resends what went out
+        let _lock = DirLock::state().await?;
+        let mut outbox = Outbox::load()?;
+        match result {
+            Ok(()) => {
+                println!("   📧 Sent: {}", notification.subject);
+                outbox.mark_delivered(&notification.key);
+                report.notified += 1;
+            }
+            Err(e) => {
+                eprintln!(
+                    "   Failed to send '{}' (attempt {}): {}",
+                    notification.subject,
+                    notification.attempts + 1,
+                    e
+                );
+                outbox.mark_failed(&notification.key, &e);
+                report.failures += 1;
+            }
+        }
+        outbox.save()?;
+    }
+    Ok(())
+}
+
+/// Re-seeds the state: either clears it (the next check records a new
+/// baseline) or records the current grades right away, without notifying.
+pub async fn reset_state(baseline: bool) -> Result<(), UqgrdError> {
+    if !baseline {
+        let _lock = DirLock::state().await?;
+        clear_state()?;
+        println!("🗑️  State cleared. The next check records a new baseline.");
+        return Ok(());
+    }
 
+    let (username, password) = auth::get_credentials()?;
+    let mut client = ApiClient::from_env()?;
+    client.login(&username, &password).await?;
+
+    let Some((trimestre, courses)) = fetch_current_courses(&client).await? else {
+        println!("   Nothing recorded; the state was left as is.");
+        return Ok(());
+    };
+
+    // Merged into the saved state, so other semesters and the change
+    // timestamps adaptive polling learns from are kept
+    let _lock = DirLock::state().await?;
+    let mut state = load_or_recover_state()?;
+    let mut recorded = 0;
+    for (activity, details) in &courses {
+        let key = CourseKey::new(trimestre, activity);
+        state.adopt(&key);
+        let course = match details {
+            Ok(data) => {
+                recorded += 1;
+                CourseState::recorded(state.get(&key), data)
+            }
+            Err(e) => {
+                eprintln!(
+                    "   Failed to fetch details for {} (recorded silently once it loads): {}",
+                    activity.sigle, e
+                );
+                CourseState::unfetched(state.get(&key))
+            }
+        };
+        state.insert(key, course);
+    }
+
+    save_state(&state)?;
+    println!("📌 Baseline recorded for {} course(s).", recorded);
     Ok(())
 }
 
+// Live details of every course of the current semester, across its programs
+async fn fetch_current_courses(
+    client: &ApiClient,
+) -> Result<Option<(u32, CourseResults)>, UqgrdError> {
+    let current_sem_code = api::get_current_semester_code();
+    let transcript = client.fetch_transcript().await?;
+
+    let Some(sem) = transcript
+        .into_iter()
+        .find(|s| s.trimestre == current_sem_code)
+    else {
+        println!(
+            "   No active semester found for code {} (Are you registered?)",
+            current_sem_code
+        );
+        return Ok(None);
+    };
+
+    let mut courses = Vec::new();
+    for prog in sem.programmes {
+        let details = client
+            .fetch_all_course_details(sem.trimestre, &prog.activites)
+            .await;
+        courses.extend(prog.activites.into_iter().zip(details));
+    }
+    Ok(Some((sem.trimestre, courses)))
+}
+
 // --- LOGIC HELPERS ---
 
-fn has_grade_changed(state: &GradesState, sigle: &str, new_data: &DetailActivity) -> bool {
-    match state.get(sigle) {
+pub fn has_grade_changed(state: &GradesState, key: &CourseKey, new_data: &DetailActivity) -> bool {
+    match state.get(key) {
+        // Recorded silently once fetched (see `CourseState::unfetched`)
+        Some(old_data) if old_data.unfetched => false,
         Some(old_data) => {
             // Compare Total (Float tolerance)
             let total_diff = match (old_data.total, new_data.total) {
@@ -150,68 +561,230 @@ fn has_grade_changed(state: &GradesState, sigle: &str, new_data: &DetailActivity
     }
 }
 
-fn load_state() -> Result<GradesState, String> {
-    let config_dir = auth::get_config_dir()?;
-    let state_path = config_dir.join(STATE_FILE);
-
-    if !state_path.exists() {
-        return Ok(HashMap::new());
-    }
+// Same key for the same transition, so a change queued before a crash is not
+// queued again when the next cycle detects it anew. The time of the previous
+// change tells apart a transition that happens again later (e.g. a total
+// corrected down and back up).
+fn change_key(state: &GradesState, key: &CourseKey, new_data: &DetailActivity) -> String {
+    let fingerprint = |total: Option<f64>, note: Option<&str>, posted: usize| {
+        format!(
+            "{}/{}/{}",
+            total.map_or("-".to_string(), |t| format!("{:.2}", t)),
+            note.unwrap_or("-"),
+            posted
+        )
+    };
 
-    let content = fs::read_to_string(state_path).map_err(|e| e.to_string())?;
-    let state: GradesState = serde_json::from_str(&content).map_err(|e| e.to_string())?;
-    Ok(state)
+    let old = match state.get(key) {
+        Some(old) => format!(
+            "{}@{}",
+            fingerprint(old.total, old.note.as_deref(), old.posted.len()),
+            old.changed_at
+                .last()
+                .map_or("-".to_string(), |at| at.timestamp().to_string())
+        ),
+        None => "new".to_string(),
+    };
+    let new = fingerprint(
+        new_data.total,
+        new_data.note.as_deref(),
+        new_data
+            .evaluations
+            .iter()
+            .filter(|e| e.is_posted())
+            .count(),
+    );
+
+    format!("grade:{}:{}->{}", key, old, new)
 }
 
-fn save_state(state: &GradesState) -> Result<(), String> {
-    let config_dir = auth::get_config_dir()?;
-    let state_path = config_dir.join(STATE_FILE);
-
-    let json = serde_json::to_string_pretty(state).map_err(|e| e.to_string())?;
-    fs::write(state_path, json).map_err(|e| e.to_string())?;
-    Ok(())
+// Evaluations posted since the last saved state (all of them for a new course)
+pub fn new_evaluations<'a>(
+    state: &GradesState,
+    key: &CourseKey,
+    new_data: &'a DetailActivity,
+) -> Vec<&'a Evaluation> {
+    let known = state.get(key).map(|old| &old.posted);
+
+    new_data
+        .evaluations
+        .iter()
+        .filter(|e| e.is_posted())
+        .filter(|e| known.is_none_or(|titles| !titles.contains(&e.titre)))
+        .collect()
 }
 
 // --- EMAILER ---
 
-fn send_email_alert(
-    username: &str,
+// Subject and body of a grade change email
+fn alert_message(
     sigle: &str,
     title: &str,
     data: &DetailActivity,
-) -> Result<(), String> {
-    // Load SMTP settings from Env Vars (Standard for Docker/Cloud)
-    let smtp_user = std::env::var("SMTP_USERNAME").map_err(|_| "SMTP_USERNAME env missing")?;
-    let smtp_pass = std::env::var("SMTP_PASSWORD").map_err(|_| "SMTP_PASSWORD env missing")?;
-    let smtp_host = std::env::var("SMTP_SERVER").unwrap_or_else(|_| "smtp.gmail.com".to_string());
-
-    let dest_email = format!("{}@uqam.ca", username);
-
+    new_evals: &[&Evaluation],
+) -> (String, String) {
     let grade_display = data.note.clone().unwrap_or("N/A".to_string());
     let total_display = match data.total {
         Some(v) => format!("{:.2}%", v),
         None => "N/A".to_string(),
     };
 
+    let mut extra = String::new();
+    if let Some(mean) = data.stats().mean {
+        extra.push_str(&format!("Group mean: {:.2}%\n", mean));
+    }
+    if !new_evals.is_empty() {
+        extra.push_str("\nNewly posted:\n");
+        for eval in new_evals {
+            extra.push_str(&format!("  - {}\n", display::describe_evaluation(eval)));
+        }
+    }
+
+    (
+        format!("UQAM Grade Update: {}", sigle),
+        format!(
+            "New grade detected!\n\nCourse: {} - {}\nGrade: {}\nTotal: {}\n{}\nCheck here: https://monportail.uqam.ca",
+            sigle, title, grade_display, total_display, extra
+        ),
+    )
+}
+
+// One email with every course of the semester, sent instead of per-course
+// alerts on the first run
+fn baseline_summary_message(trimestre: u32, courses: &CourseResults) -> (String, String) {
+    let mut lines = String::new();
+    for (activity, details) in courses {
+        let (total, note) = match details {
+            Ok(data) => (
+                display::fmt_percent(data.total),
+                data.note.clone().unwrap_or("N/A".to_string()),
+            ),
+            Err(_) => ("unavailable".to_string(), "---".to_string()),
+        };
+        lines.push_str(&format!(
+            "  - {} {}: {} ({})\n",
+            activity.sigle, activity.titre, note, total
+        ));
+    }
+
+    (
+        format!(
+            "UQAM Grades: now watching {}",
+            api::format_semester_name(trimestre)
+        ),
+        format!(
+            "uqgrd is now watching your grades. Current results:\n\n{}\nYou will get an email whenever one of them changes.\n\nCheck here: https://monportail.uqam.ca",
+            lines
+        ),
+    )
+}
+
+// Tells the student the daemon is stuck on something it cannot fix itself
+async fn send_error_alert(error: &UqgrdError) -> Result<(), UqgrdError> {
+    let username = auth::load_config()?.username;
+
+    send_email(
+        &username,
+        "UQGRD needs attention",
+        format!(
+            "The grades daemon stopped being able to check your grades:\n\n{}\n\nIt will keep retrying, but you may need to update your credentials or uqgrd itself.",
+            error
+        ),
+    )
+    .await
+}
+
+// SMTP delivery blocks, so it runs on the blocking pool instead of stalling
+// the runtime (and the signal listener)
+async fn send_email(username: &str, subject: &str, body: String) -> Result<(), UqgrdError> {
+    let (username, subject) = (username.to_string(), subject.to_string());
+    tokio::task::spawn_blocking(move || send_email_blocking(&username, &subject, body))
+        .await
+        .map_err(|e| UqgrdError::Smtp(e.to_string()))?
+}
+
+fn send_email_blocking(username: &str, subject: &str, body: String) -> Result<(), UqgrdError> {
+    // Load SMTP settings from Env Vars (Standard for Docker/Cloud)
+    let smtp_user = std::env::var("SMTP_USERNAME")
+        .map_err(|_| UqgrdError::Config("SMTP_USERNAME env missing".into()))?;
+    let smtp_pass = std::env::var("SMTP_PASSWORD")
+        .map_err(|_| UqgrdError::Config("SMTP_PASSWORD env missing".into()))?;
+    let smtp_host = std::env::var("SMTP_SERVER").unwrap_or_else(|_| "smtp.gmail.com".to_string());
+
+    let dest_email = format!("{}@uqam.ca", username);
+
     let email = Message::builder()
-        .from(smtp_user.parse().map_err(|_| "Invalid sender")?)
-        .to(dest_email.parse().map_err(|_| "Invalid recipient")?)
-        .subject(format!("UQAM Grade Update: {}", sigle))
-        .body(format!(
-            "New grade detected!\n\nCourse: {} - {}\nGrade: {}\nTotal: {}\n\nCheck here: https://monportail.uqam.ca",
-            sigle, title, grade_display, total_display
-        ))
-        .map_err(|e| e.to_string())?;
+        .from(
+            smtp_user
+                .parse()
+                .map_err(|_| UqgrdError::Config("Invalid sender".into()))?,
+        )
+        .to(dest_email
+            .parse()
+            .map_err(|_| UqgrdError::Config("Invalid recipient".into()))?)
+        .subject(subject)
+        .body(body)
+        .map_err(|e| UqgrdError::Smtp(e.to_string()))?;
 
     let creds = Credentials::new(smtp_user, smtp_pass);
 
     // Using relay() allows automatic upgrading to TLS (StartTLS) on port 587
     let mailer = SmtpTransport::relay(&smtp_host)
-        .map_err(|e| e.to_string())?
+        .map_err(|e| UqgrdError::Smtp(e.to_string()))?
         .credentials(creds)
         .build();
 
-    mailer.send(&email).map_err(|e| e.to_string())?;
+    mailer
+        .send(&email)
+        .map_err(|e| UqgrdError::Smtp(e.to_string()))?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn detail(total: f64, note: &str) -> DetailActivity {
+        serde_json::from_value(json!({ "total": total, "note": note })).unwrap()
+    }
+
+    // Records `data` the way a check cycle does and returns the alert key
+    fn detect(state: &mut GradesState, key: &CourseKey, data: &DetailActivity) -> String {
+        let change = change_key(state, key, data);
+        let updated = CourseState::changed(state.get(key), data);
+        state.insert(key.clone(), updated);
+        change
+    }
+
+    #[test]
+    fn change_key_is_stable_until_the_state_is_saved() {
+        let key: CourseKey = "20263/INF1120/10".parse().unwrap();
+        let mut state = GradesState::default();
+        state.insert(key.clone(), CourseState::from_detail(&detail(70.0, "B")));
+
+        // A crash before the state was saved detects the same change again
+        let b = detail(81.5, "A-");
+        assert_eq!(change_key(&state, &key, &b), change_key(&state, &key, &b));
+        assert!(change_key(&state, &key, &b).starts_with("grade:20263/INF1120/10:70.00/B/0@-"));
+    }
+
+    #[test]
+    fn change_key_differs_when_a_grade_comes_back() {
+        let key: CourseKey = "20263/INF1120/10".parse().unwrap();
+        let (a, b) = (detail(70.0, "B"), detail(81.5, "A-"));
+        let mut state = GradesState::default();
+        state.insert(key.clone(), CourseState::from_detail(&a));
+
+        let first = detect(&mut state, &key, &b);
+        let back = detect(&mut state, &key, &a);
+        let again = detect(&mut state, &key, &b);
+        assert_ne!(first, back);
+        assert_ne!(first, again);
+
+        let mut outbox = Outbox::default();
+        assert!(outbox.enqueue(first, String::new(), String::new()));
+        assert!(outbox.enqueue(again, String::new(), String::new()));
+    }
+}
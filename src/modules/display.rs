@@ -0,0 +1,514 @@
+use crate::constants::DEFAULT_COURSE_CREDITS;
+use crate::modules::api::{
+    format_semester_name, Activity, DetailActivity, Evaluation, SemesterResult,
+};
+use crate::modules::daemon::{self, FirstRun};
+use crate::modules::error::UqgrdError;
+use crate::modules::gpa::{self, Gpa, SemesterGpa};
+use crate::modules::history::HistoryEntry;
+use crate::modules::state::{CourseKey, GradesState};
+use crate::modules::whatif::{Outcome, Projection, Simulation, Target};
+use chrono::Local;
+use std::collections::BTreeMap;
+
+pub fn fmt_percent(value: Option<f64>) -> String {
+    match value {
+        Some(v) => format!("{:.2}%", v),
+        None => "N/A".to_string(),
+    }
+}
+
+fn fmt_signed(value: Option<f64>) -> String {
+    match value {
+        Some(v) => format!("{:+.1}", v),
+        None => "—".to_string(),
+    }
+}
+
+fn fmt_mean(value: Option<f64>) -> String {
+    match value {
+        Some(v) => format!("{:.1}%", v),
+        None => "—".to_string(),
+    }
+}
+
+/// Course table for one semester/program, in the order of `activities`.
+/// Rows flagged in `changed` (same order) are highlighted.
+pub fn print_grades_table(
+    activities: &[Activity],
+    details: &[Result<DetailActivity, UqgrdError>],
+    changed: &[bool],
+) {
+    println!(
+        "{:<10} | {:<40} | {:<10} | {:<5} | {:>7} | {:>6} | {:>5}",
+        "Sigle", "Title", "Total (%)", "Grade", "Mean", "Δ", "z"
+    );
+    println!(
+        "{:-<10}-|-{:-<40}-|-{:-<10}-|-{:-<5}-|-{:->7}-|-{:->6}-|-{:->5}",
+        "", "", "", "", "", "", ""
+    );
+
+    for (i, (activity, details)) in activities.iter().zip(details).enumerate() {
+        match details {
+            Ok(det) if changed.get(i).copied().unwrap_or(false) => {
+                let stats = det.stats();
+                // Bold yellow, with a marker after the last column
+                println!(
+                    "\x1b[1;33m{:<10} | {:<40} | {:<10} | {:<5} | {:>7} | {:>6} | {:>5} ●\x1b[0m",
+                    activity.sigle,
+                    activity.titre.chars().take(40).collect::<String>(),
+                    fmt_percent(det.total),
+                    det.note.as_deref().unwrap_or("N/A"),
+                    fmt_mean(stats.mean),
+                    fmt_signed(stats.delta()),
+                    fmt_signed(stats.z_score())
+                );
+            }
+            Ok(det) => {
+                let stats = det.stats();
+                println!(
+                    "{:<10} | {:<40} | {:<10} | {:<5} | {:>7} | {:>6} | {:>5}",
+                    activity.sigle,
+                    activity.titre.chars().take(40).collect::<String>(),
+                    fmt_percent(det.total),
+                    det.note.as_deref().unwrap_or("N/A"),
+                    fmt_mean(stats.mean),
+                    fmt_signed(stats.delta()),
+                    fmt_signed(stats.z_score())
+                );
+            }
+            Err(_) => {
+                println!(
+                    "{:<10} | {:<40} | {:<10} | {:<5} | {:>7} | {:>6} | {:>5}",
+                    activity.sigle,
+                    activity.titre.chars().take(40).collect::<String>(),
+                    "ERROR",
+                    "---",
+                    "",
+                    "",
+                    ""
+                );
+            }
+        }
+    }
+}
+
+/// Saved daemon state next to the live portal data, flagging the courses
+/// the next daemon cycle would notify about.
+pub fn print_state_diff(
+    state: &GradesState,
+    first_run: FirstRun,
+    trimestre: u32,
+    activities: &[Activity],
+    details: &[Result<DetailActivity, UqgrdError>],
+) {
+    println!(
+        "{:<10} | {:>10} | {:<5} | {:>10} | {:<5} | {:<16}",
+        "Sigle", "Saved", "Grade", "Live", "Grade", "Status"
+    );
+    println!(
+        "{:-<10}-|-{:->10}-|-{:-<5}-|-{:->10}-|-{:-<5}-|-{:-<16}",
+        "", "", "", "", "", ""
+    );
+
+    // Without saved state the next cycle only records the current grades
+    let baseline = first_run.records_baseline(state);
+    let mut pending = 0;
+    for (activity, details) in activities.iter().zip(details) {
+        let key = CourseKey::new(trimestre, activity);
+        let saved = state.get(&key);
+        let (saved_total, saved_note) = match saved {
+            Some(s) => (fmt_percent(s.total), s.note.as_deref().unwrap_or("N/A")),
+            None => ("—".to_string(), "—"),
+        };
+
+        match details {
+            Ok(det) => {
+                let course_baseline = baseline || state.awaits_baseline(&key);
+                let notify = !course_baseline && daemon::has_grade_changed(state, &key, det);
+                let status = match (notify, saved) {
+                    _ if course_baseline => "baseline (no notification)",
+                    (true, _) => "🔔 would notify",
+                    (false, None) => "not tracked yet",
+                    (false, Some(_)) => "unchanged",
+                };
+                println!(
+                    "{:<10} | {:>10} | {:<5} | {:>10} | {:<5} | {:<16}",
+                    activity.sigle,
+                    saved_total,
+                    saved_note,
+                    fmt_percent(det.total),
+                    det.note.as_deref().unwrap_or("N/A"),
+                    status
+                );
+
+                if notify {
+                    pending += 1;
+                    for eval in daemon::new_evaluations(state, &key, det) {
+                        println!("{:<10}   📝 {}", "", describe_evaluation(eval));
+                    }
+                }
+            }
+            Err(e) => {
+                println!(
+                    "{:<10} | {:>10} | {:<5} | {:>10} | {:<5} | {:<16}",
+                    activity.sigle, saved_total, saved_note, "ERROR", "---", "fetch failed"
+                );
+                println!("{:<10}   {}", "", e);
+            }
+        }
+    }
+
+    println!();
+    match pending {
+        _ if baseline && first_run == FirstRun::Summary => println!(
+            "📌 No saved state: the next check records these grades and sends one summary email."
+        ),
+        _ if baseline => {
+            println!("📌 No saved state: the next check records these grades without notifying.")
+        }
+        0 => println!("✅ The daemon would not send any notification."),
+        n => println!("🔔 {} course(s) would trigger a notification.", n),
+    }
+}
+
+/// How each course's total and letter evolved, one table per course.
+pub fn print_timeline(entries: &[HistoryEntry]) {
+    let mut by_course: BTreeMap<&CourseKey, Vec<&HistoryEntry>> = BTreeMap::new();
+    for entry in entries {
+        by_course.entry(&entry.key).or_default().push(entry);
+    }
+
+    for (key, changes) in by_course {
+        println!(
+            "\n📈 {} — {} (group {})\n",
+            key.sigle,
+            format_semester_name(key.trimestre),
+            key.groupe
+        );
+        println!(
+            "{:<16} | {:<10} | {:<5} | {:>6} | {:<30}",
+            "Date", "Total (%)", "Grade", "Δ", "Posted"
+        );
+        println!(
+            "{:-<16}-|-{:-<10}-|-{:-<5}-|-{:->6}-|-{:-<30}",
+            "", "", "", "", ""
+        );
+
+        let mut previous: Option<f64> = None;
+        for change in changes {
+            let delta = match (previous, change.total) {
+                (Some(old), Some(new)) => Some(new - old),
+                _ => None,
+            };
+            println!(
+                "{:<16} | {:<10} | {:<5} | {:>6} | {:<30}",
+                change.at.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+                fmt_percent(change.total),
+                change.note.as_deref().unwrap_or("N/A"),
+                fmt_signed(delta),
+                change.posted.join(", ")
+            );
+            previous = change.total.or(previous);
+        }
+    }
+    println!();
+}
+
+/// Evaluation-by-evaluation breakdown of a single course.
+pub fn print_course_detail(semester: u32, activity: &Activity, detail: &DetailActivity) {
+    println!(
+        "\n📖 {} - {} (group {}, {})\n",
+        activity.sigle,
+        activity.titre,
+        activity.groupe,
+        format_semester_name(semester)
+    );
+
+    let facts = activity_facts(activity);
+    if !facts.is_empty() {
+        println!("{}\n", facts.join(" · "));
+    }
+
+    if detail.evaluations.is_empty() {
+        println!("No evaluations published for this course yet.");
+    } else {
+        println!(
+            "{:<30} | {:>7} | {:>13} | {:>8} | {:>12} | {:>7} | {:>6} | {:>5} | {:<10}",
+            "Evaluation", "Weight", "Score", "%", "Contribution", "Mean", "Δ", "z", "Posted"
+        );
+        println!(
+            "{:-<30}-|-{:->7}-|-{:->13}-|-{:->8}-|-{:->12}-|-{:->7}-|-{:->6}-|-{:->5}-|-{:-<10}",
+            "", "", "", "", "", "", "", "", ""
+        );
+
+        for eval in &detail.evaluations {
+            let weight = match eval.ponderation {
+                Some(w) => format!("{:.1}%", w),
+                None => "N/A".to_string(),
+            };
+            let score = match (eval.note, eval.note_maximale) {
+                (Some(n), Some(max)) => format!("{:.2}/{:.0}", n, max),
+                (Some(n), None) => format!("{:.2}", n),
+                (None, _) => "—".to_string(),
+            };
+            let percentage = match eval.percentage() {
+                Some(p) => format!("{:.1}%", p),
+                None => "—".to_string(),
+            };
+            let contribution = match eval.contribution() {
+                Some(c) => format!("{:.2}", c),
+                None => "—".to_string(),
+            };
+            let stats = eval.stats();
+
+            println!(
+                "{:<30} | {:>7} | {:>13} | {:>8} | {:>12} | {:>7} | {:>6} | {:>5} | {:<10}",
+                eval.titre.chars().take(30).collect::<String>(),
+                weight,
+                score,
+                percentage,
+                contribution,
+                fmt_mean(stats.mean),
+                fmt_signed(stats.delta()),
+                fmt_signed(stats.z_score()),
+                eval.date_diffusion.as_deref().unwrap_or("—")
+            );
+        }
+
+        let posted: Vec<_> = detail
+            .evaluations
+            .iter()
+            .filter(|e| e.is_posted())
+            .collect();
+        let weight_posted: f64 = posted.iter().filter_map(|e| e.ponderation).sum();
+        let earned: f64 = posted.iter().filter_map(|e| e.contribution()).sum();
+
+        println!(
+            "\n{} of {} evaluations posted ({:.1}% of the grade): {:.2} points earned",
+            posted.len(),
+            detail.evaluations.len(),
+            weight_posted,
+            earned
+        );
+    }
+
+    let stats = detail.stats();
+    println!(
+        "Total: {}   Grade: {}   Group mean: {}   Δ: {}   z: {}\n",
+        fmt_percent(detail.total),
+        detail.note.as_deref().unwrap_or("N/A"),
+        fmt_mean(stats.mean),
+        fmt_signed(stats.delta()),
+        fmt_signed(stats.z_score())
+    );
+}
+
+// Transcript metadata worth showing above a course breakdown
+fn activity_facts(activity: &Activity) -> Vec<String> {
+    let mut facts = Vec::new();
+
+    if let Some(credits) = activity.credits {
+        facts.push(format!("{} credits", credits));
+    }
+    if let Some(kind) = &activity.type_activite {
+        facts.push(kind.clone());
+    }
+    if let Some(status) = &activity.statut {
+        facts.push(status.to_string());
+    }
+    if let Some(mention) = &activity.mention {
+        facts.push(format!("Mention: {}", mention));
+    }
+    match (activity.date_debut, activity.date_fin) {
+        (Some(start), Some(end)) => facts.push(format!("{} → {}", start, end)),
+        (Some(start), None) => facts.push(format!("Starts {}", start)),
+        (None, Some(end)) => facts.push(format!("Ends {}", end)),
+        (None, None) => {}
+    }
+
+    facts
+}
+
+/// One-line summary of an evaluation, used in notifications.
+pub fn describe_evaluation(eval: &Evaluation) -> String {
+    let stats = eval.stats();
+    let mut line = format!(
+        "{}: {} (weight {})",
+        eval.titre,
+        match eval.percentage() {
+            Some(p) => format!("{:.1}%", p),
+            None => "N/A".to_string(),
+        },
+        match eval.ponderation {
+            Some(w) => format!("{:.1}%", w),
+            None => "N/A".to_string(),
+        }
+    );
+
+    if let Some(mean) = stats.mean {
+        line.push_str(&format!(", group mean {:.1}%", mean));
+    }
+    if let Some(delta) = stats.delta() {
+        line.push_str(&format!(", Δ {:+.1}", delta));
+    }
+    if let Some(z) = stats.z_score() {
+        line.push_str(&format!(", z {:+.2}", z));
+    }
+    line
+}
+
+/// Per-semester GPA with the running cumulative average.
+pub fn print_gpa_table(rows: &[SemesterGpa]) {
+    if rows.is_empty() {
+        println!("No graded courses yet.\n");
+        return;
+    }
+
+    println!(
+        "{:<14} | {:>7} | {:>8} | {:>10}",
+        "Semester", "Credits", "Term GPA", "Cumulative"
+    );
+    println!("{:-<14}-|-{:->7}-|-{:->8}-|-{:->10}", "", "", "", "");
+
+    for row in rows {
+        println!(
+            "{:<14} | {:>7} | {:>8} | {:>10}",
+            format_semester_name(row.trimestre),
+            row.term.credits,
+            fmt_gpa(row.term.average()),
+            fmt_gpa(row.cumulative.average())
+        );
+    }
+
+    if let Some(last) = rows.last() {
+        println!(
+            "\nCumulative GPA: {} over {} credits\n",
+            fmt_gpa(last.cumulative.average()),
+            last.cumulative.credits
+        );
+    }
+}
+
+fn fmt_gpa(value: Option<f64>) -> String {
+    match value {
+        Some(v) => format!("{:.2}", v),
+        None => "N/A".to_string(),
+    }
+}
+
+/// Every semester/program of the transcript, oldest first, with term averages.
+pub fn print_history(transcript: &[SemesterResult]) {
+    let mut semesters: Vec<&SemesterResult> = transcript.iter().collect();
+    semesters.sort_by_key(|s| s.trimestre);
+
+    let mut cumulative = Gpa::default();
+    let mut total_credits = 0.0;
+
+    for sem in semesters {
+        for prog in &sem.programmes {
+            println!(
+                "\n📅 {} — {} {}\n",
+                format_semester_name(sem.trimestre),
+                prog.code_prog,
+                prog.titre_programme
+            );
+            println!(
+                "{:<10} | {:<40} | {:>7} | {:<5} | {:<10}",
+                "Sigle", "Title", "Credits", "Grade", "Status"
+            );
+            println!(
+                "{:-<10}-|-{:-<40}-|-{:->7}-|-{:-<5}-|-{:-<10}",
+                "", "", "", "", ""
+            );
+
+            let mut term = Gpa::default();
+            let mut credits = 0.0;
+
+            for activity in &prog.activites {
+                term.add(activity);
+                if gpa::is_earned(activity) {
+                    credits += activity.credits.unwrap_or(DEFAULT_COURSE_CREDITS);
+                }
+
+                println!(
+                    "{:<10} | {:<40} | {:>7} | {:<5} | {:<10}",
+                    activity.sigle,
+                    activity.titre.chars().take(40).collect::<String>(),
+                    activity
+                        .credits
+                        .map(|c| c.to_string())
+                        .unwrap_or("—".into()),
+                    activity.note.as_deref().unwrap_or("—"),
+                    activity
+                        .statut
+                        .as_ref()
+                        .map(|s| s.to_string())
+                        .unwrap_or_default()
+                );
+            }
+
+            cumulative.merge(&term);
+            total_credits += credits;
+            println!(
+                "\n{} courses · {} credits earned · Term GPA: {}",
+                prog.activites.len(),
+                credits,
+                fmt_gpa(term.average())
+            );
+        }
+    }
+
+    println!(
+        "\nTotal: {} credits earned · Cumulative GPA: {} ({} graded credits)\n",
+        total_credits,
+        fmt_gpa(cumulative.average()),
+        cumulative.credits
+    );
+}
+
+/// Average needed on the remaining evaluations for each target.
+pub fn print_whatif(
+    activity: &Activity,
+    projection: &Projection,
+    targets: &[Target],
+    simulations: &[Simulation],
+) {
+    println!("\n🎯 What-if: {} - {}\n", activity.sigle, activity.titre);
+
+    for sim in simulations {
+        println!("   Simulating {} at {:.1}%", sim.name, sim.score);
+    }
+    if !projection.unweighted.is_empty() {
+        println!(
+            "   ⚠️  Ignored (no published weight): {}",
+            projection.unweighted.join(", ")
+        );
+    }
+
+    println!(
+        "Secured: {:.2} pts · Remaining weight: {:.1}% · Possible final: {:.2}% – {:.2}%\n",
+        projection.earned,
+        projection.remaining_weight,
+        projection.total_with(0.0),
+        projection.total_with(100.0)
+    );
+
+    println!("{:<7} | {:>7} | Needed on remaining", "Target", "Cutoff");
+    println!("{:-<7}-|-{:->7}-|-{:-<20}", "", "", "");
+
+    for target in targets {
+        let needed = match projection.outcome(target.cutoff) {
+            Outcome::Guaranteed => "✅ guaranteed".to_string(),
+            Outcome::Unreachable => "❌ out of reach".to_string(),
+            Outcome::Needs(avg) => format!("📈 {:.1}% average", avg),
+        };
+        println!(
+            "{:<7} | {:>7} | {}",
+            target.label,
+            format!("{:.1}%", target.cutoff),
+            needed
+        );
+    }
+    println!();
+}
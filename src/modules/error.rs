@@ -0,0 +1,87 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub enum UqgrdError {
+    /// The portal refused the username/password pair.
+    AuthRejected,
+    /// The bearer token was refused (401/403) on an authenticated endpoint.
+    TokenExpired,
+    /// Connection, DNS, TLS or timeout failure before a response arrived.
+    Network(String),
+    /// The portal answered with a non-success status.
+    Http {
+        status: u16,
+        url: String,
+    },
+    /// The response did not match the expected JSON schema.
+    Parse(String),
+    Keyring(String),
+    Config(String),
+    Smtp(String),
+    /// Local file I/O (state file, config directory).
+    Io(String),
+    /// Interactive prompt failure or invalid user input.
+    Input(String),
+    /// Another uqgrd process holds the state directory lock.
+    Locked(String),
+}
+
+impl UqgrdError {
+    /// Process exit code used by `main` for this kind of failure.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            UqgrdError::Config(_) => 3,
+            UqgrdError::Keyring(_) => 4,
+            UqgrdError::AuthRejected => 5,
+            UqgrdError::TokenExpired => 6,
+            UqgrdError::Network(_) => 7,
+            UqgrdError::Http { .. } => 8,
+            UqgrdError::Parse(_) => 9,
+            UqgrdError::Smtp(_) => 10,
+            UqgrdError::Io(_) => 11,
+            UqgrdError::Input(_) => 12,
+            UqgrdError::Locked(_) => 13,
+        }
+    }
+
+    /// Failures that may go away on their own (outage, rate limiting, 5xx).
+    pub fn is_transient(&self) -> bool {
+        match self {
+            UqgrdError::Network(_) | UqgrdError::Locked(_) => true,
+            UqgrdError::Http { status, .. } => *status == 429 || *status >= 500,
+            _ => false,
+        }
+    }
+
+    /// The session is stale and logging in again should fix it.
+    pub fn needs_reauth(&self) -> bool {
+        matches!(self, UqgrdError::TokenExpired)
+    }
+
+    /// Failures that will not fix themselves and need the user to act.
+    pub fn needs_attention(&self) -> bool {
+        matches!(self, UqgrdError::AuthRejected | UqgrdError::Parse(_))
+    }
+}
+
+impl fmt::Display for UqgrdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UqgrdError::AuthRejected => {
+                write!(f, "Authentication rejected (check your credentials)")
+            }
+            UqgrdError::TokenExpired => write!(f, "Session token expired or was refused"),
+            UqgrdError::Network(e) => write!(f, "Network error: {}", e),
+            UqgrdError::Http { status, url } => write!(f, "HTTP {} from {}", status, url),
+            UqgrdError::Parse(e) => write!(f, "Unexpected response format: {}", e),
+            UqgrdError::Keyring(e) => write!(f, "Keyring error: {}", e),
+            UqgrdError::Config(e) => write!(f, "Configuration error: {}", e),
+            UqgrdError::Smtp(e) => write!(f, "Email error: {}", e),
+            UqgrdError::Io(e) => write!(f, "I/O error: {}", e),
+            UqgrdError::Input(e) => write!(f, "Input error: {}", e),
+            UqgrdError::Locked(e) => write!(f, "State directory locked: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for UqgrdError {}
@@ -0,0 +1,129 @@
+use crate::constants::DEFAULT_COURSE_CREDITS;
+use crate::modules::api::{Activity, ActivityStatus, SemesterResult};
+
+// UQAM letter scale (4.3). Anything not listed here, such as S (succès),
+// E (échec), X (abandon), I (incomplet), K (exemption), Z (reprise) or an
+// empty in-progress grade, carries no grade points and is left out.
+const LETTER_SCALE: &[(&str, f64)] = &[
+    ("A+", 4.3),
+    ("A", 4.0),
+    ("A-", 3.7),
+    ("B+", 3.3),
+    ("B", 3.0),
+    ("B-", 2.7),
+    ("C+", 2.3),
+    ("C", 2.0),
+    ("C-", 1.7),
+    ("D+", 1.3),
+    ("D", 1.0),
+];
+
+pub fn grade_points(letter: &str) -> Option<f64> {
+    let letter = letter.trim().to_uppercase();
+    LETTER_SCALE
+        .iter()
+        .find(|(l, _)| *l == letter)
+        .map(|(_, points)| *points)
+}
+
+// Passing letters that carry no grade points: S (succès), K (exemption)
+const PASSING_WITHOUT_POINTS: &[&str] = &["S", "K"];
+
+/// Whether the activity's credits were earned: a "Réussite" status, or a
+/// passing letter when the status says nothing more specific.
+pub fn is_earned(activity: &Activity) -> bool {
+    match activity.statut {
+        Some(ActivityStatus::Reussite) => true,
+        Some(ActivityStatus::Echec | ActivityStatus::Abandon) => false,
+        _ => activity.note.as_deref().is_some_and(|letter| {
+            grade_points(letter).is_some()
+                || PASSING_WITHOUT_POINTS.contains(&letter.trim().to_uppercase().as_str())
+        }),
+    }
+}
+
+/// Credit-weighted accumulator of grade points.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Gpa {
+    pub credits: f64,
+    pub quality_points: f64,
+}
+
+impl Gpa {
+    /// Counts the activity if its letter is on the scale. Returns whether it did.
+    pub fn add(&mut self, activity: &Activity) -> bool {
+        if activity.is_abandoned() {
+            return false;
+        }
+        let Some(points) = activity.note.as_deref().and_then(grade_points) else {
+            return false;
+        };
+        let credits = activity.credits.unwrap_or(DEFAULT_COURSE_CREDITS);
+
+        self.credits += credits;
+        self.quality_points += points * credits;
+        true
+    }
+
+    pub fn merge(&mut self, other: &Gpa) {
+        self.credits += other.credits;
+        self.quality_points += other.quality_points;
+    }
+
+    pub fn average(&self) -> Option<f64> {
+        (self.credits > 0.0).then(|| self.quality_points / self.credits)
+    }
+}
+
+pub struct SemesterGpa {
+    pub trimestre: u32,
+    pub term: Gpa,
+    pub cumulative: Gpa,
+}
+
+/// Per-semester and running cumulative GPA, oldest semester first.
+/// `program` restricts the computation to one program code.
+pub fn by_semester(transcript: &[SemesterResult], program: Option<&str>) -> Vec<SemesterGpa> {
+    let mut semesters: Vec<&SemesterResult> = transcript.iter().collect();
+    semesters.sort_by_key(|s| s.trimestre);
+
+    let mut cumulative = Gpa::default();
+    let mut rows = Vec::new();
+
+    for sem in semesters {
+        let mut term = Gpa::default();
+        let activities = sem
+            .programmes
+            .iter()
+            .filter(|p| program.is_none_or(|code| p.code_prog == code))
+            .flat_map(|p| &p.activites);
+
+        let mut counted = false;
+        for activity in activities {
+            counted |= term.add(activity);
+        }
+        if !counted {
+            continue;
+        }
+
+        cumulative.merge(&term);
+        rows.push(SemesterGpa {
+            trimestre: sem.trimestre,
+            term,
+            cumulative,
+        });
+    }
+
+    rows
+}
+
+/// Distinct (code, title) of every program in the transcript, newest first.
+pub fn programs(transcript: &[SemesterResult]) -> Vec<(String, String)> {
+    let mut seen: Vec<(String, String)> = Vec::new();
+    for prog in transcript.iter().flat_map(|s| &s.programmes) {
+        if !seen.iter().any(|(code, _)| *code == prog.code_prog) {
+            seen.push((prog.code_prog.clone(), prog.titre_programme.clone()));
+        }
+    }
+    seen
+}
@@ -0,0 +1,95 @@
+use crate::constants::HISTORY_FILE;
+use crate::modules::api::{DetailActivity, Evaluation};
+use crate::modules::auth;
+use crate::modules::error::UqgrdError;
+use crate::modules::state::CourseKey;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// One observed change of a course, as appended to `grades_history.jsonl`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HistoryEntry {
+    pub at: DateTime<Utc>,
+    pub key: CourseKey,
+    pub total: Option<f64>,
+    pub note: Option<String>,
+    /// Titles of the evaluations posted with this change.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub posted: Vec<String>,
+}
+
+impl HistoryEntry {
+    pub fn new(key: CourseKey, data: &DetailActivity, new_evals: &[&Evaluation]) -> Self {
+        Self {
+            at: Utc::now(),
+            key,
+            total: data.total,
+            note: data.note.clone(),
+            posted: new_evals.iter().map(|e| e.titre.clone()).collect(),
+        }
+    }
+}
+
+/// Appends entries, one JSON object per line. Existing lines are never rewritten.
+pub fn append(entries: &[HistoryEntry]) -> Result<(), UqgrdError> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    let config_dir = auth::get_config_dir()?;
+    fs::create_dir_all(&config_dir).map_err(|e| UqgrdError::Io(e.to_string()))?;
+
+    let path = config_dir.join(HISTORY_FILE);
+    let mut lines = String::new();
+    // Keep a line cut short by a crash from swallowing the first new entry
+    if !ends_with_newline(&path) {
+        lines.push('\n');
+    }
+    for entry in entries {
+        let json = serde_json::to_string(entry).map_err(|e| UqgrdError::Io(e.to_string()))?;
+        lines.push_str(&json);
+        lines.push('\n');
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| UqgrdError::Io(e.to_string()))?;
+    file.write_all(lines.as_bytes())
+        .map_err(|e| UqgrdError::Io(e.to_string()))
+}
+
+fn ends_with_newline(path: &Path) -> bool {
+    let mut last = [0u8; 1];
+    match File::open(path) {
+        Ok(mut file) => match file.seek(SeekFrom::End(-1)) {
+            Ok(_) => file.read_exact(&mut last).is_ok() && last[0] == b'\n',
+            // Empty file
+            Err(_) => true,
+        },
+        // Not created yet
+        Err(_) => true,
+    }
+}
+
+/// Every recorded entry, oldest first. Unreadable lines (e.g. cut short by a
+/// crash) are skipped.
+pub fn load() -> Result<Vec<HistoryEntry>, UqgrdError> {
+    let path = auth::get_config_dir()?.join(HISTORY_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(path).map_err(|e| UqgrdError::Io(e.to_string()))?;
+    let mut entries: Vec<HistoryEntry> = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    entries.sort_by_key(|e| e.at);
+    Ok(entries)
+}
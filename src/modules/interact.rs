@@ -1,4 +1,5 @@
-use crate::modules::api::{Programme, SemesterResult, format_semester_name};
+use crate::modules::api::{format_semester_name, Programme, SemesterResult};
+use crate::modules::error::UqgrdError;
 use inquire::{Password, PasswordDisplayMode, Select, Text};
 use std::fmt;
 
@@ -17,9 +18,11 @@ impl fmt::Display for MenuOption {
     }
 }
 
-pub fn select_semester(
-    history: &[SemesterResult],
-) -> Result<Option<(&SemesterResult, &Programme)>, String> {
+/// Menu of every semester and program, limited to `program` when given.
+pub fn select_semester<'a>(
+    history: &'a [SemesterResult],
+    program: Option<&str>,
+) -> Result<Option<(&'a SemesterResult, &'a Programme)>, UqgrdError> {
     let mut options = Vec::new();
 
     // Build the list of choices
@@ -27,6 +30,9 @@ pub fn select_semester(
         let sem_name = format_semester_name(semester.trimestre);
 
         for (i, prog) in semester.programmes.iter().enumerate() {
+            if program.is_some_and(|code| prog.code_prog != code) {
+                continue;
+            }
             let label = format!("{} - {}", sem_name, prog.titre_programme);
             options.push(MenuOption {
                 label,
@@ -37,12 +43,15 @@ pub fn select_semester(
     }
 
     if options.is_empty() {
-        return Err("No semesters found.".to_string());
+        return Err(UqgrdError::Input(match program {
+            Some(code) => format!("No semesters found for program {}.", code),
+            None => "No semesters found.".to_string(),
+        }));
     }
 
     let selection = Select::new("Select a semester:", options)
         .prompt()
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| UqgrdError::Input(e.to_string()))?;
 
     // Find the original data based on selection
     // (We search by code to get the reference back)
@@ -56,17 +65,17 @@ pub fn select_semester(
 }
 
 // We return a Result containing a tuple (username, password) or an error
-pub fn prompt_credentials() -> Result<(String, String), String> {
+pub fn prompt_credentials() -> Result<(String, String), UqgrdError> {
     let username = Text::new("Enter UQAM Username (Code permanent)")
         .prompt()
         .map(|u| u.trim().to_string())
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| UqgrdError::Input(e.to_string()))?;
 
     let password = Password::new("Enter Password:")
         .without_confirmation()
         .with_display_mode(PasswordDisplayMode::Masked)
         .prompt()
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| UqgrdError::Input(e.to_string()))?;
 
     Ok((username, password))
 }
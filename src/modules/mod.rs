@@ -1,4 +1,17 @@
 pub mod api;
 pub mod auth;
+pub mod common;
 pub mod daemon;
+pub mod display;
+pub mod error;
+pub mod gpa;
+pub mod history;
 pub mod interact;
+pub mod outbox;
+pub mod output;
+pub mod retry;
+pub mod schedule;
+pub mod state;
+pub mod tui;
+pub mod watch;
+pub mod whatif;
@@ -0,0 +1,197 @@
+use crate::constants::{
+    OUTBOX_DELIVERED_RETENTION_DAYS, OUTBOX_FILE, OUTBOX_RETRY_BASE_MINUTES,
+    OUTBOX_RETRY_MAX_MINUTES,
+};
+use crate::modules::auth;
+use crate::modules::common;
+use crate::modules::error::UqgrdError;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+
+/// An email waiting to be delivered.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Notification {
+    /// Idempotency key: the same event always gets the same key.
+    pub key: String,
+    pub subject: String,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_attempt_at: Option<DateTime<Utc>>,
+}
+
+impl Notification {
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.next_attempt_at.is_none_or(|at| at <= now)
+    }
+}
+
+/// Notifications queued on disk (`outbox.json`) until the mail server accepts them.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Outbox {
+    #[serde(default)]
+    pub pending: Vec<Notification>,
+    /// Keys of delivered notifications, so an event is never sent twice.
+    #[serde(default)]
+    pub delivered: BTreeMap<String, DateTime<Utc>>,
+}
+
+impl Outbox {
+    /// Missing file means an empty outbox; a corrupt one is moved aside.
+    pub fn load() -> Result<Self, UqgrdError> {
+        let path = auth::get_config_dir()?.join(OUTBOX_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path).map_err(|e| UqgrdError::Io(e.to_string()))?;
+        match serde_json::from_str(&content) {
+            Ok(outbox) => Ok(outbox),
+            Err(e) => {
+                let backup = common::move_aside(&path)?;
+                eprintln!("⚠️  Outbox file was corrupt ({})", e);
+                eprintln!("   Moved it to {} and starting empty", backup.display());
+                Ok(Self::default())
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<(), UqgrdError> {
+        let path = auth::get_config_dir()?.join(OUTBOX_FILE);
+        let json = serde_json::to_string_pretty(self).map_err(|e| UqgrdError::Io(e.to_string()))?;
+        common::write_atomic(&path, json.as_bytes())
+    }
+
+    /// Queues a notification unless one with the same key is pending or was
+    /// already delivered. Returns whether it was queued.
+    pub fn enqueue(&mut self, key: String, subject: String, body: String) -> bool {
+        if self.delivered.contains_key(&key) || self.pending.iter().any(|n| n.key == key) {
+            return false;
+        }
+
+        self.pending.push(Notification {
+            key,
+            subject,
+            body,
+            created_at: Utc::now(),
+            attempts: 0,
+            last_error: None,
+            next_attempt_at: None,
+        });
+        true
+    }
+
+    /// Pending notifications whose retry delay has elapsed, oldest first,
+    /// leased for `OUTBOX_RETRY_BASE_MINUTES` so a concurrent cycle does not
+    /// send them too. If the process dies before recording the attempt, the
+    /// lease runs out and they are sent again.
+    pub fn claim_due(&mut self, now: DateTime<Utc>) -> Vec<Notification> {
+        let lease = now + Duration::minutes(OUTBOX_RETRY_BASE_MINUTES);
+        let mut due = Vec::new();
+        for n in self.pending.iter_mut().filter(|n| n.is_due(now)) {
+            due.push(n.clone());
+            n.next_attempt_at = Some(lease);
+        }
+        due
+    }
+
+    /// Earliest scheduled retry of a failed notification.
+    pub fn next_retry(&self) -> Option<DateTime<Utc>> {
+        self.pending.iter().filter_map(|n| n.next_attempt_at).min()
+    }
+
+    pub fn mark_delivered(&mut self, key: &str) {
+        self.pending.retain(|n| n.key != key);
+        self.delivered.insert(key.to_string(), Utc::now());
+    }
+
+    /// Records a failed attempt and schedules the next one with exponential
+    /// backoff (capped at `OUTBOX_RETRY_MAX_MINUTES`).
+    pub fn mark_failed(&mut self, key: &str, error: &UqgrdError) {
+        if let Some(n) = self.pending.iter_mut().find(|n| n.key == key) {
+            n.attempts += 1;
+            n.last_error = Some(error.to_string());
+
+            let factor = 2i64.saturating_pow(n.attempts.saturating_sub(1));
+            let delay = OUTBOX_RETRY_BASE_MINUTES
+                .saturating_mul(factor)
+                .min(OUTBOX_RETRY_MAX_MINUTES);
+            n.next_attempt_at = Some(Utc::now() + Duration::minutes(delay));
+        }
+    }
+
+    /// Forgets delivered keys past their retention. Pending notifications are
+    /// never dropped: the state already marks their change as seen, so this
+    /// is the only record that the student still has to hear about it.
+    pub fn prune(&mut self, now: DateTime<Utc>) {
+        let retention = Duration::days(OUTBOX_DELIVERED_RETENTION_DAYS);
+        self.delivered.retain(|_, at| now - *at <= retention);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue(outbox: &mut Outbox, key: &str) -> bool {
+        outbox.enqueue(key.to_string(), "subject".into(), "body".into())
+    }
+
+    #[test]
+    fn enqueue_skips_pending_and_delivered_keys() {
+        let mut outbox = Outbox::default();
+        assert!(queue(&mut outbox, "a"));
+        assert!(!queue(&mut outbox, "a"));
+        assert_eq!(outbox.pending.len(), 1);
+
+        outbox.mark_delivered("a");
+        assert!(outbox.pending.is_empty());
+        assert!(!queue(&mut outbox, "a"));
+        assert!(queue(&mut outbox, "b"));
+    }
+
+    #[test]
+    fn mark_failed_backs_off_up_to_the_cap() {
+        let mut outbox = Outbox::default();
+        queue(&mut outbox, "a");
+        let error = UqgrdError::Smtp("refused".into());
+        let delay = |outbox: &Outbox| {
+            let at = outbox.pending[0].next_attempt_at.unwrap();
+            (at - Utc::now()).num_minutes() + 1
+        };
+
+        outbox.mark_failed("a", &error);
+        assert_eq!(delay(&outbox), OUTBOX_RETRY_BASE_MINUTES);
+        outbox.mark_failed("a", &error);
+        assert_eq!(delay(&outbox), OUTBOX_RETRY_BASE_MINUTES * 2);
+
+        for _ in 0..40 {
+            outbox.mark_failed("a", &error);
+        }
+        assert_eq!(delay(&outbox), OUTBOX_RETRY_MAX_MINUTES);
+        assert_eq!(outbox.pending[0].attempts, 42);
+        assert_eq!(
+            outbox.pending[0].last_error.as_deref(),
+            Some("Email error: refused")
+        );
+    }
+
+    #[test]
+    fn claimed_notifications_are_not_due_again() {
+        let mut outbox = Outbox::default();
+        queue(&mut outbox, "a");
+        let now = Utc::now();
+
+        assert_eq!(outbox.claim_due(now).len(), 1);
+        assert!(outbox.claim_due(now).is_empty());
+        // The lease runs out if the attempt was never recorded
+        let later = now + Duration::minutes(OUTBOX_RETRY_BASE_MINUTES);
+        assert_eq!(outbox.claim_due(later).len(), 1);
+    }
+}
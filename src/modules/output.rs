@@ -0,0 +1,165 @@
+use crate::modules::api::{DetailActivity, Programme};
+use crate::modules::error::UqgrdError;
+use clap::ValueEnum;
+use serde::Serialize;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Table,
+    Json,
+    Csv,
+    Markdown,
+    Yaml,
+}
+
+/// One activity of the grades command, flattened for export.
+#[derive(Serialize, Debug)]
+pub struct GradeRecord {
+    pub semester: u32,
+    pub program: String,
+    pub sigle: String,
+    pub title: String,
+    pub group: u32,
+    pub total: Option<f64>,
+    pub letter: Option<String>,
+    /// Why the details could not be fetched (null on success).
+    pub error: Option<String>,
+}
+
+const COLUMNS: [&str; 8] = [
+    "semester", "program", "sigle", "title", "group", "total", "letter", "error",
+];
+
+impl GradeRecord {
+    fn cells(&self) -> [String; 8] {
+        [
+            self.semester.to_string(),
+            self.program.clone(),
+            self.sigle.clone(),
+            self.title.clone(),
+            self.group.to_string(),
+            self.total.map(|t| format!("{:.2}", t)).unwrap_or_default(),
+            self.letter.clone().unwrap_or_default(),
+            self.error.clone().unwrap_or_default(),
+        ]
+    }
+}
+
+pub fn grade_records(
+    semester: u32,
+    program: &Programme,
+    details: &[Result<DetailActivity, UqgrdError>],
+) -> Vec<GradeRecord> {
+    program
+        .activites
+        .iter()
+        .zip(details)
+        .map(|(activity, detail)| {
+            let (total, letter, error) = match detail {
+                Ok(det) => (det.total, det.note.clone(), None),
+                Err(e) => (None, None, Some(e.to_string())),
+            };
+
+            GradeRecord {
+                semester,
+                program: program.code_prog.clone(),
+                sigle: activity.sigle.clone(),
+                title: activity.titre.clone(),
+                group: activity.groupe,
+                total,
+                letter,
+                error,
+            }
+        })
+        .collect()
+}
+
+/// Renders records in a machine-readable format. `Table` is handled by
+/// `display::print_grades_table` and renders nothing here.
+pub fn render(format: OutputFormat, records: &[GradeRecord]) -> Result<String, UqgrdError> {
+    Ok(match format {
+        OutputFormat::Table => String::new(),
+        OutputFormat::Json => {
+            serde_json::to_string_pretty(records)
+                .map_err(|e| UqgrdError::Io(format!("JSON output: {}", e)))?
+                + "\n"
+        }
+        OutputFormat::Csv => render_csv(records),
+        OutputFormat::Markdown => render_markdown(records),
+        OutputFormat::Yaml => render_yaml(records),
+    })
+}
+
+fn render_csv(records: &[GradeRecord]) -> String {
+    let mut out = COLUMNS.join(",");
+    out.push('\n');
+
+    for record in records {
+        let row: Vec<String> = record.cells().iter().map(|c| csv_escape(c)).collect();
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+fn csv_escape(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+fn render_markdown(records: &[GradeRecord]) -> String {
+    let mut out = format!("| {} |\n", COLUMNS.join(" | "));
+    out.push_str(&format!("|{}\n", " --- |".repeat(COLUMNS.len())));
+
+    for record in records {
+        let row: Vec<String> = record
+            .cells()
+            .iter()
+            .map(|c| c.replace('|', "\\|"))
+            .collect();
+        out.push_str(&format!("| {} |\n", row.join(" | ")));
+    }
+    out
+}
+
+fn render_yaml(records: &[GradeRecord]) -> String {
+    if records.is_empty() {
+        return "[]\n".to_string();
+    }
+
+    let mut out = String::new();
+    for r in records {
+        out.push_str(&format!("- semester: {}\n", r.semester));
+        out.push_str(&format!("  program: {}\n", yaml_string(&r.program)));
+        out.push_str(&format!("  sigle: {}\n", yaml_string(&r.sigle)));
+        out.push_str(&format!("  title: {}\n", yaml_string(&r.title)));
+        out.push_str(&format!("  group: {}\n", r.group));
+        out.push_str(&format!(
+            "  total: {}\n",
+            r.total
+                .map(|t| format!("{:.2}", t))
+                .unwrap_or("null".into())
+        ));
+        out.push_str(&format!(
+            "  letter: {}\n",
+            r.letter
+                .as_deref()
+                .map(yaml_string)
+                .unwrap_or("null".into())
+        ));
+        out.push_str(&format!(
+            "  error: {}\n",
+            r.error.as_deref().map(yaml_string).unwrap_or("null".into())
+        ));
+    }
+    out
+}
+
+// A JSON string literal is also a valid double-quoted YAML scalar
+fn yaml_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "\"\"".to_string())
+}
@@ -0,0 +1,127 @@
+use crate::constants::{
+    DEFAULT_RETRY_BASE_DELAY_MS, DEFAULT_RETRY_MAX_ATTEMPTS, DEFAULT_RETRY_MAX_DELAY_MS,
+    DEFAULT_RETRY_STATUSES,
+};
+use crate::modules::common::{self, Log};
+use crate::modules::error::UqgrdError;
+use rand::Rng;
+use std::env;
+use std::future::Future;
+use std::time::Duration;
+
+/// How many times, and how patiently, a portal request is retried.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total attempts including the first one (1 disables retries).
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Randomize each delay so parallel requests don't retry in lockstep.
+    pub jitter: bool,
+    pub retryable_statuses: Vec<u16>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_RETRY_MAX_ATTEMPTS,
+            base_delay: Duration::from_millis(DEFAULT_RETRY_BASE_DELAY_MS),
+            max_delay: Duration::from_millis(DEFAULT_RETRY_MAX_DELAY_MS),
+            jitter: true,
+            retryable_statuses: parse_statuses(DEFAULT_RETRY_STATUSES),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn from_env() -> Self {
+        let statuses = env::var("RETRY_STATUSES")
+            .map(|v| parse_statuses(&v))
+            .unwrap_or_else(|_| parse_statuses(DEFAULT_RETRY_STATUSES));
+
+        Self {
+            max_attempts: common::env_or("RETRY_MAX_ATTEMPTS", DEFAULT_RETRY_MAX_ATTEMPTS).max(1),
+            base_delay: Duration::from_millis(common::env_or(
+                "RETRY_BASE_DELAY_MS",
+                DEFAULT_RETRY_BASE_DELAY_MS,
+            )),
+            max_delay: Duration::from_millis(common::env_or(
+                "RETRY_MAX_DELAY_MS",
+                DEFAULT_RETRY_MAX_DELAY_MS,
+            )),
+            jitter: common::env_or("RETRY_JITTER", true),
+            retryable_statuses: statuses,
+        }
+    }
+
+    pub fn should_retry(&self, error: &UqgrdError) -> bool {
+        match error {
+            UqgrdError::Network(_) => true,
+            UqgrdError::Http { status, .. } => self.retryable_statuses.contains(status),
+            _ => false,
+        }
+    }
+
+    /// Exponential backoff for the given (1-based) failed attempt, capped at
+    /// `max_delay`. With jitter the delay is drawn from [delay/2, delay].
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let delay = self.base_delay.saturating_mul(factor).min(self.max_delay);
+
+        if self.jitter && !delay.is_zero() {
+            let half = delay / 2;
+            half + rand::rng().random_range(Duration::ZERO..=half)
+        } else {
+            delay
+        }
+    }
+
+    /// Runs `op` until it succeeds, fails with a non-retryable error, or the
+    /// attempts are exhausted. Each retry is logged to `log` with its attempt number.
+    pub async fn run<T, F, Fut>(&self, context: &str, log: &Log, mut op: F) -> Result<T, UqgrdError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, UqgrdError>>,
+    {
+        let mut attempt = 1;
+
+        loop {
+            match op().await {
+                Err(e) if attempt < self.max_attempts && self.should_retry(&e) => {
+                    let delay = self.delay_for(attempt);
+                    log.emit(format!(
+                        "   ↻ {} failed (attempt {}/{}): {}. Retrying in {:.1}s",
+                        context,
+                        attempt,
+                        self.max_attempts,
+                        e,
+                        delay.as_secs_f64()
+                    ));
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    if attempt > 1 {
+                        log.emit(format!(
+                            "   ✖ {} gave up after {} attempts",
+                            context, attempt
+                        ));
+                    }
+                    return Err(e);
+                }
+                Ok(value) => {
+                    if attempt > 1 {
+                        log.emit(format!("   ✔ {} succeeded on attempt {}", context, attempt));
+                    }
+                    return Ok(value);
+                }
+            }
+        }
+    }
+}
+
+fn parse_statuses(list: &str) -> Vec<u16> {
+    list.split(',')
+        .filter_map(|code| code.trim().parse().ok())
+        .collect()
+}
@@ -0,0 +1,796 @@
+use crate::constants::{
+    ADAPTIVE_LEARN_DAYS, DEFAULT_ADAPTIVE_FAST_MINUTES, DEFAULT_ADAPTIVE_RECENT_HOURS,
+    DEFAULT_ADAPTIVE_SLOW_MINUTES, DEFAULT_CHECK_INTERVAL_MINUTES, DEFAULT_CHECK_JITTER_SECS,
+    DEFAULT_POSTING_PERIODS,
+};
+use crate::modules::common;
+use crate::modules::error::UqgrdError;
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Local, NaiveTime, Timelike, Utc};
+use rand::Rng;
+use std::env;
+use std::time::Duration;
+
+// Upper bound when searching for the next cron match (a leap year of minutes)
+const CRON_SEARCH_MINUTES: i64 = 366 * 24 * 60;
+
+// Names accepted in the month and day-of-week cron fields
+const CRON_MONTHS: &[&str] = &[
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const CRON_DAYS: &[&str] = &["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// When the daemon wakes up, from `CHECK_SCHEDULE` (falling back to `CHECK_INTERVAL`).
+///
+/// `CHECK_SCHEDULE` is either a 5-field cron expression (`*/10 8-21 * * 1-5`)
+/// or `;`-separated windows where the first match wins:
+/// `mon-fri 08:00-22:00 10m; 1h`, or `adaptive`.
+#[derive(Debug, Clone)]
+pub enum Schedule {
+    Every(Duration),
+    Windows {
+        windows: Vec<Window>,
+        /// Interval outside every window.
+        fallback: Duration,
+    },
+    Cron(Cron),
+    Adaptive(Adaptive),
+}
+
+impl Schedule {
+    pub fn from_env() -> Result<Self, UqgrdError> {
+        let fallback = Duration::from_secs(
+            common::env_or("CHECK_INTERVAL", DEFAULT_CHECK_INTERVAL_MINUTES).max(1) * 60,
+        );
+
+        match env::var("CHECK_SCHEDULE") {
+            Ok(spec) if !spec.trim().is_empty() => Self::parse(&spec, fallback)
+                .map_err(|e| UqgrdError::Config(format!("Invalid CHECK_SCHEDULE: {}", e))),
+            _ => Ok(Schedule::Every(fallback)),
+        }
+    }
+
+    pub fn parse(spec: &str, fallback: Duration) -> Result<Self, String> {
+        let spec = spec.trim();
+        // A window has at most 3 tokens, so a single 5-field rule can only be cron
+        let cron_like = !spec.contains(';') && spec.split_whitespace().count() == 5;
+
+        if cron_like {
+            return Cron::parse(spec).map(Schedule::Cron);
+        }
+        if spec.eq_ignore_ascii_case("adaptive") {
+            return Adaptive::from_env(fallback).map(Schedule::Adaptive);
+        }
+
+        let windows = spec
+            .split(';')
+            .map(str::trim)
+            .filter(|rule| !rule.is_empty())
+            .map(Window::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Schedule::Windows { windows, fallback })
+    }
+
+    /// When the next check should start (before jitter).
+    pub fn next_after(&self, now: DateTime<Local>) -> DateTime<Local> {
+        match self {
+            Schedule::Every(every) => now + to_chrono(*every),
+            Schedule::Cron(cron) => cron.next_after(now),
+            Schedule::Adaptive(adaptive) => now + to_chrono(adaptive.interval(now).0),
+            Schedule::Windows { windows, fallback } => {
+                let every_at = |at: DateTime<Local>| {
+                    windows
+                        .iter()
+                        .find(|w| w.contains(at))
+                        .map_or(*fallback, |w| w.every)
+                };
+
+                let current = every_at(now);
+                let candidate = now + to_chrono(current);
+
+                // Wake up early if a faster window opens before the candidate
+                let mut at = next_minute(now);
+                while at < candidate {
+                    if every_at(at) < current {
+                        return at;
+                    }
+                    at += ChronoDuration::minutes(1);
+                }
+                candidate
+            }
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Schedule::Every(every) => format!("Every {} minutes", every.as_secs() / 60),
+            Schedule::Cron(cron) => format!("Cron '{}'", cron.spec),
+            Schedule::Adaptive(adaptive) => format!(
+                "Adaptive (fast {}, normal {}, slow {})",
+                fmt_duration(adaptive.fast),
+                fmt_duration(adaptive.normal),
+                fmt_duration(adaptive.slow)
+            ),
+            Schedule::Windows { windows, fallback } => {
+                let mut rules: Vec<String> = windows.iter().map(|w| w.spec.clone()).collect();
+                if !windows.iter().any(Window::is_catch_all) {
+                    rules.push(format!("otherwise every {}", fmt_duration(*fallback)));
+                }
+                rules.join("; ")
+            }
+        }
+    }
+
+    /// Why the next interval was picked, for schedules that decide at runtime.
+    pub fn explain(&self, now: DateTime<Local>) -> Option<String> {
+        match self {
+            Schedule::Adaptive(adaptive) => {
+                let (every, reason) = adaptive.interval(now);
+                Some(format!(
+                    "Adaptive: every {} ({})",
+                    fmt_duration(every),
+                    reason
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// Feeds the change timestamps from the state to schedules that learn from them.
+    pub fn observe(&mut self, changes: &[DateTime<Utc>]) {
+        if let Schedule::Adaptive(adaptive) = self {
+            adaptive.changes = changes.to_vec();
+        }
+    }
+}
+
+/// Polls fast right after a change and during grade-posting periods, at the
+/// normal interval around dates where changes happened in past years, and
+/// slowly otherwise (breaks, mid-session).
+#[derive(Debug, Clone)]
+pub struct Adaptive {
+    fast: Duration,
+    normal: Duration,
+    slow: Duration,
+    /// How long the fast rate is kept after a change.
+    recent: ChronoDuration,
+    periods: Vec<Period>,
+    changes: Vec<DateTime<Utc>>,
+}
+
+impl Adaptive {
+    fn from_env(normal: Duration) -> Result<Self, String> {
+        let minutes =
+            |key: &str, default: u64| Duration::from_secs(common::env_or(key, default).max(1) * 60);
+        let periods = env::var("POSTING_PERIODS")
+            .unwrap_or_else(|_| DEFAULT_POSTING_PERIODS.to_string())
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(Period::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            fast: minutes("ADAPTIVE_FAST_INTERVAL", DEFAULT_ADAPTIVE_FAST_MINUTES),
+            normal,
+            slow: minutes("ADAPTIVE_SLOW_INTERVAL", DEFAULT_ADAPTIVE_SLOW_MINUTES),
+            recent: ChronoDuration::hours(common::env_or(
+                "ADAPTIVE_RECENT_HOURS",
+                DEFAULT_ADAPTIVE_RECENT_HOURS,
+            )),
+            periods,
+            changes: Vec::new(),
+        })
+    }
+
+    /// Interval to use at `now`, with the reason it was picked.
+    fn interval(&self, now: DateTime<Local>) -> (Duration, String) {
+        let now_utc = now.with_timezone(&Utc);
+
+        if let Some(last) = self.changes.iter().max() {
+            let age = now_utc - *last;
+            if age < self.recent {
+                return (
+                    self.fast,
+                    format!("last change {}h ago", age.num_hours().max(0)),
+                );
+            }
+        }
+
+        if self.periods.iter().any(|p| p.contains(now)) {
+            return (self.fast, "grade-posting period".to_string());
+        }
+
+        // Changes from earlier sessions that happened around this time of year
+        let learned = self
+            .changes
+            .iter()
+            .filter(|c| now_utc - **c > ChronoDuration::days(30))
+            .filter(|c| day_distance(c.ordinal(), now.ordinal()) <= ADAPTIVE_LEARN_DAYS)
+            .count();
+        if learned > 0 {
+            return (
+                self.normal,
+                format!("{} past change(s) around this date", learned),
+            );
+        }
+
+        (self.slow, "break or mid-session".to_string())
+    }
+}
+
+/// A yearly date range (inclusive), possibly wrapping over New Year.
+#[derive(Debug, Clone, Copy)]
+struct Period {
+    start: (u32, u32),
+    end: (u32, u32),
+}
+
+impl Period {
+    // `MM-DD:MM-DD`
+    fn parse(spec: &str) -> Result<Self, String> {
+        let (start, end) = spec
+            .split_once(':')
+            .ok_or_else(|| format!("'{}' is not a MM-DD:MM-DD period", spec))?;
+        Ok(Self {
+            start: parse_month_day(start)?,
+            end: parse_month_day(end)?,
+        })
+    }
+
+    fn contains(&self, at: DateTime<Local>) -> bool {
+        let day = (at.month(), at.day());
+        if self.start <= self.end {
+            day >= self.start && day <= self.end
+        } else {
+            day >= self.start || day <= self.end
+        }
+    }
+}
+
+/// A set of days and hours polled at a given interval.
+#[derive(Debug, Clone)]
+pub struct Window {
+    spec: String,
+    /// Indexed from Monday (0) to Sunday (6).
+    days: [bool; 7],
+    hours: Option<(NaiveTime, NaiveTime)>,
+    every: Duration,
+}
+
+impl Window {
+    /// `[DAYS] [HH:MM-HH:MM] INTERVAL`, e.g. `mon-fri 08:00-22:00 10m` or `sat,sun 2h`.
+    fn parse(rule: &str) -> Result<Self, String> {
+        let mut days = [true; 7];
+        let mut hours = None;
+        let mut every = None;
+        let mut has_days = false;
+
+        for token in rule.split_whitespace() {
+            let starts_with_digit = token.starts_with(|c: char| c.is_ascii_digit());
+            let (kind, repeated) = if starts_with_digit && token.contains('-') {
+                (
+                    "hour range",
+                    hours.replace(parse_time_range(token)?).is_some(),
+                )
+            } else if starts_with_digit {
+                ("interval", every.replace(parse_duration(token)?).is_some())
+            } else {
+                days = parse_days(token)?;
+                ("day list", std::mem::replace(&mut has_days, true))
+            };
+            // Keeping the last one would silently run a different schedule
+            if repeated {
+                return Err(format!(
+                    "'{}' has more than one {} ('{}')",
+                    rule, kind, token
+                ));
+            }
+        }
+
+        Ok(Self {
+            spec: rule.to_string(),
+            days,
+            hours,
+            every: every.ok_or_else(|| format!("'{}' has no interval (e.g. 10m)", rule))?,
+        })
+    }
+
+    fn is_catch_all(&self) -> bool {
+        self.days == [true; 7] && self.hours.is_none()
+    }
+
+    fn contains(&self, at: DateTime<Local>) -> bool {
+        let day = at.weekday().num_days_from_monday() as usize;
+        self.days[day]
+            && self
+                .hours
+                .is_none_or(|(start, end)| time_in_range(at.time(), start, end))
+    }
+}
+
+/// Standard 5-field cron expression (minute hour day-of-month month day-of-week).
+#[derive(Debug, Clone)]
+pub struct Cron {
+    spec: String,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    // Cron ORs day-of-month and day-of-week when both are restricted
+    dom_any: bool,
+    dow_any: bool,
+}
+
+impl Cron {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = spec.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!("'{}' needs 5 fields", spec));
+        }
+
+        let mut days_of_week = parse_cron_field(fields[4], 0, 7, CRON_DAYS)?;
+        // 7 is an alias for Sunday
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week |= 1;
+        }
+
+        Ok(Self {
+            spec: spec.to_string(),
+            minutes: parse_cron_field(fields[0], 0, 59, &[])?,
+            hours: parse_cron_field(fields[1], 0, 23, &[])?,
+            days_of_month: parse_cron_field(fields[2], 1, 31, &[])?,
+            months: parse_cron_field(fields[3], 1, 12, CRON_MONTHS)?,
+            days_of_week,
+            dom_any: fields[2] == "*",
+            dow_any: fields[4] == "*",
+        })
+    }
+
+    fn matches(&self, at: DateTime<Local>) -> bool {
+        let bit = |set: u64, value: u32| set & (1 << value) != 0;
+
+        let dom = bit(self.days_of_month, at.day());
+        let dow = bit(self.days_of_week, at.weekday().num_days_from_sunday());
+        let day = match (self.dom_any, self.dow_any) {
+            (true, true) => true,
+            (true, false) => dow,
+            (false, true) => dom,
+            (false, false) => dom || dow,
+        };
+
+        day && bit(self.minutes, at.minute())
+            && bit(self.hours, at.hour())
+            && bit(self.months, at.month())
+    }
+
+    fn next_after(&self, now: DateTime<Local>) -> DateTime<Local> {
+        let mut at = next_minute(now);
+        for _ in 0..CRON_SEARCH_MINUTES {
+            if self.matches(at) {
+                return at;
+            }
+            at += ChronoDuration::minutes(1);
+        }
+        // Never matches (e.g. Feb 30): check daily rather than never
+        now + ChronoDuration::days(1)
+    }
+}
+
+/// Hours during which checks run but notifications wait, from `QUIET_HOURS`
+/// (e.g. `22:00-07:00`).
+#[derive(Debug, Clone, Copy)]
+pub struct QuietHours {
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl QuietHours {
+    pub fn from_env() -> Result<Option<Self>, UqgrdError> {
+        match env::var("QUIET_HOURS") {
+            Ok(spec) if !spec.trim().is_empty() => parse_time_range(spec.trim())
+                .map(|(start, end)| Some(Self { start, end }))
+                .map_err(|e| UqgrdError::Config(format!("Invalid QUIET_HOURS: {}", e))),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn contains(&self, at: DateTime<Local>) -> bool {
+        time_in_range(at.time(), self.start, self.end)
+    }
+
+    /// The next moment quiet hours are over.
+    pub fn end_after(&self, now: DateTime<Local>) -> DateTime<Local> {
+        let mut at = next_minute(now);
+        while self.contains(at) {
+            at += ChronoDuration::minutes(1);
+        }
+        at
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "{}-{}",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+}
+
+/// Random extra delay in [0, CHECK_JITTER] seconds added to every wait.
+pub fn jitter() -> Duration {
+    let max: u64 = common::env_or("CHECK_JITTER", DEFAULT_CHECK_JITTER_SECS);
+    if max == 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_secs(rand::rng().random_range(0..=max))
+}
+
+// --- PARSING HELPERS ---
+
+// `names` are aliases for the values from `min` up (e.g. `jan` for 1)
+fn parse_cron_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64, String> {
+    let mut set = 0u64;
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (
+                range,
+                step.parse::<u32>()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(|| format!("bad step in '{}'", part))?,
+            ),
+            None => (part, 1),
+        };
+
+        let (lo, hi) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (
+                parse_cron_value(a, min, max, names)?,
+                parse_cron_value(b, min, max, names)?,
+            )
+        } else {
+            let v = parse_cron_value(range, min, max, names)?;
+            // `5/15` means "from 5 to the end, every 15"
+            (v, if part.contains('/') { max } else { v })
+        };
+
+        if lo > hi {
+            return Err(format!("empty range '{}'", part));
+        }
+        for v in (lo..=hi).step_by(step as usize) {
+            set |= 1 << v;
+        }
+    }
+
+    Ok(set)
+}
+
+fn parse_cron_value(value: &str, min: u32, max: u32, names: &[&str]) -> Result<u32, String> {
+    let lower = value.to_lowercase();
+    if let Some(i) = names.iter().position(|n| *n == lower) {
+        return Ok(min + i as u32);
+    }
+    if value.starts_with(|c: char| c.is_alphabetic()) {
+        return Err(format!("unknown name '{}'", value));
+    }
+
+    value
+        .parse()
+        .ok()
+        .filter(|v| (min..=max).contains(v))
+        .ok_or_else(|| format!("'{}' is outside {}-{}", value, min, max))
+}
+
+// `10m`, `1h`, `90s`; a bare number is minutes
+fn parse_duration(token: &str) -> Result<Duration, String> {
+    let (number, unit) = token.split_at(token.trim_end_matches(char::is_alphabetic).len());
+    let value: u64 = number
+        .parse()
+        .map_err(|_| format!("'{}' is not an interval", token))?;
+
+    let secs = match unit {
+        "s" => value,
+        "" | "m" | "min" => value * 60,
+        "h" => value * 3600,
+        _ => return Err(format!("unknown unit in '{}' (use s, m or h)", token)),
+    };
+    if secs == 0 {
+        return Err(format!("'{}' must be greater than zero", token));
+    }
+    Ok(Duration::from_secs(secs))
+}
+
+// `08:00-22:00` or `8h-22h`; the end is exclusive and may wrap past midnight
+fn parse_time_range(token: &str) -> Result<(NaiveTime, NaiveTime), String> {
+    let (start, end) = token
+        .split_once('-')
+        .ok_or_else(|| format!("'{}' is not a time range", token))?;
+    let (start, end) = (parse_time(start)?, parse_time(end)?);
+    // Includes 00:00-24:00, since 24:00 is midnight: leave out the hours to
+    // cover the whole day instead
+    if start == end {
+        return Err(format!("'{}' is an empty time range", token));
+    }
+    Ok((start, end))
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime, String> {
+    let value = value.trim().trim_end_matches('h');
+    let (h, m) = value.split_once([':', 'h']).unwrap_or((value, "0"));
+    let m = if m.is_empty() { "0" } else { m };
+
+    match (h.parse::<u32>(), m.parse::<u32>()) {
+        // 24:00 is accepted as the end of the day
+        (Ok(24), Ok(0)) => Ok(NaiveTime::MIN),
+        (Ok(h), Ok(m)) => {
+            NaiveTime::from_hms_opt(h, m, 0).ok_or_else(|| format!("'{}' is not a time", value))
+        }
+        _ => Err(format!("'{}' is not a time", value)),
+    }
+}
+
+// `mon-fri`, `sat,sun`, `weekdays`, `weekends`, `*` (French names work too)
+fn parse_days(token: &str) -> Result<[bool; 7], String> {
+    let mut days = [false; 7];
+
+    for part in token.to_lowercase().split(',') {
+        match part {
+            "*" | "daily" => days = [true; 7],
+            "weekdays" => days[..5].fill(true),
+            "weekends" => days[5..].fill(true),
+            _ => {
+                let (a, b) = part.split_once('-').unwrap_or((part, part));
+                let (a, b) = (day_index(a)?, day_index(b)?);
+                // Wraps around the week, e.g. `fri-mon`
+                let mut d = a;
+                loop {
+                    days[d] = true;
+                    if d == b {
+                        break;
+                    }
+                    d = (d + 1) % 7;
+                }
+            }
+        }
+    }
+
+    Ok(days)
+}
+
+fn day_index(name: &str) -> Result<usize, String> {
+    const NAMES: [[&str; 2]; 7] = [
+        ["mon", "lun"],
+        ["tue", "mar"],
+        ["wed", "mer"],
+        ["thu", "jeu"],
+        ["fri", "ven"],
+        ["sat", "sam"],
+        ["sun", "dim"],
+    ];
+
+    NAMES
+        .iter()
+        .position(|aliases| aliases.iter().any(|a| name.starts_with(a)))
+        .ok_or_else(|| format!("'{}' is not a day", name))
+}
+
+fn parse_month_day(value: &str) -> Result<(u32, u32), String> {
+    let parsed = value.trim().split_once('-').and_then(|(m, d)| {
+        let (m, d) = (m.parse::<u32>().ok()?, d.parse::<u32>().ok()?);
+        ((1..=12).contains(&m) && (1..=31).contains(&d)).then_some((m, d))
+    });
+    parsed.ok_or_else(|| format!("'{}' is not a MM-DD date", value))
+}
+
+// Days between two days of the year, going around New Year if shorter
+fn day_distance(a: u32, b: u32) -> u32 {
+    let diff = a.abs_diff(b);
+    diff.min(365 - diff.min(365))
+}
+
+fn time_in_range(time: NaiveTime, start: NaiveTime, end: NaiveTime) -> bool {
+    if start <= end {
+        time >= start && time < end
+    } else {
+        time >= start || time < end
+    }
+}
+
+fn next_minute(now: DateTime<Local>) -> DateTime<Local> {
+    let truncated = now
+        .with_second(0)
+        .and_then(|t| t.with_nanosecond(0))
+        .unwrap_or(now);
+    truncated + ChronoDuration::minutes(1)
+}
+
+fn to_chrono(duration: Duration) -> ChronoDuration {
+    ChronoDuration::from_std(duration).unwrap_or(ChronoDuration::MAX)
+}
+
+pub fn fmt_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        s if s % 3600 == 0 => format!("{}h", s / 3600),
+        s if s % 60 == 0 => format!("{}m", s / 60),
+        s => format!("{}s", s),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+    }
+
+    fn values(set: u64) -> Vec<u32> {
+        (0..64).filter(|v| set & (1 << v) != 0).collect()
+    }
+
+    fn cron(spec: &str) -> Cron {
+        match Schedule::parse(spec, Duration::from_secs(3600)).unwrap() {
+            Schedule::Cron(cron) => cron,
+            other => panic!("'{}' parsed as {:?}", spec, other),
+        }
+    }
+
+    #[test]
+    fn cron_steps_and_ranges() {
+        assert_eq!(
+            values(parse_cron_field("*/15", 0, 59, &[]).unwrap()),
+            [0, 15, 30, 45]
+        );
+        assert_eq!(
+            values(parse_cron_field("5/20", 0, 59, &[]).unwrap()),
+            [5, 25, 45]
+        );
+        assert_eq!(
+            values(parse_cron_field("10-20/5", 0, 59, &[]).unwrap()),
+            [10, 15, 20]
+        );
+        assert_eq!(
+            values(parse_cron_field("1-3,7", 0, 23, &[]).unwrap()),
+            [1, 2, 3, 7]
+        );
+
+        assert!(parse_cron_field("5-1", 0, 59, &[]).is_err());
+        assert!(parse_cron_field("*/0", 0, 59, &[]).is_err());
+        assert!(parse_cron_field("60", 0, 59, &[]).is_err());
+    }
+
+    #[test]
+    fn cron_names() {
+        let named = cron("30 8 * jan-mar mon-fri");
+        let numeric = cron("30 8 * 1-3 1-5");
+        assert_eq!(named.months, numeric.months);
+        assert_eq!(named.days_of_week, numeric.days_of_week);
+
+        // Sunday as 7 is the same as 0
+        assert_eq!(cron("0 0 * * 7").days_of_week & 1, 1);
+
+        assert!(Schedule::parse("30 8 * * mon-fry", Duration::from_secs(3600)).is_err());
+    }
+
+    #[test]
+    fn cron_day_of_month_or_day_of_week() {
+        // 2026-10-01 is a Thursday, 2026-10-05 a Monday
+        let both = cron("0 12 1 * 1");
+        assert!(both.matches(at(2026, 10, 1, 12, 0)));
+        assert!(both.matches(at(2026, 10, 5, 12, 0)));
+        assert!(!both.matches(at(2026, 10, 6, 12, 0)));
+
+        let dow_only = cron("0 12 * * 1");
+        assert!(!dow_only.matches(at(2026, 10, 1, 12, 0)));
+        assert!(dow_only.matches(at(2026, 10, 5, 12, 0)));
+    }
+
+    #[test]
+    fn cron_next_after() {
+        assert_eq!(
+            cron("*/15 * * * *").next_after(at(2026, 10, 16, 10, 7)),
+            at(2026, 10, 16, 10, 15)
+        );
+        // Friday morning, past 8:30: next run is Monday
+        assert_eq!(
+            cron("30 8 * * mon-fri").next_after(at(2026, 10, 16, 9, 0)),
+            at(2026, 10, 19, 8, 30)
+        );
+        // Never matches: falls back to a daily check
+        assert_eq!(
+            cron("0 0 30 2 *").next_after(at(2026, 10, 16, 9, 0)),
+            at(2026, 10, 17, 9, 0)
+        );
+    }
+
+    #[test]
+    fn window_rejects_repeated_tokens() {
+        assert!(Window::parse("mon 5m 10m").is_err());
+        assert!(Window::parse("mon tue 5m").is_err());
+        assert!(Window::parse("8h-10h 9h-11h 5m").is_err());
+        assert!(Window::parse("mon-fri").is_err());
+    }
+
+    #[test]
+    fn windows_wrap_around() {
+        assert_eq!(
+            parse_days("fri-mon").unwrap(),
+            [true, false, false, false, true, true, true]
+        );
+
+        // 2026-10-17 is a Saturday, 2026-10-20 a Tuesday
+        let night = Window::parse("fri-mon 22:00-06:00 5m").unwrap();
+        assert!(night.contains(at(2026, 10, 17, 23, 0)));
+        assert!(night.contains(at(2026, 10, 17, 3, 0)));
+        assert!(!night.contains(at(2026, 10, 17, 12, 0)));
+        assert!(!night.contains(at(2026, 10, 20, 23, 0)));
+    }
+
+    #[test]
+    fn windows_next_after() {
+        let schedule =
+            Schedule::parse("mon-fri 08:00-22:00 10m", Duration::from_secs(3600)).unwrap();
+        // 2026-10-19 is a Monday
+        assert_eq!(
+            schedule.next_after(at(2026, 10, 19, 12, 0)),
+            at(2026, 10, 19, 12, 10)
+        );
+        // Wakes up when the faster window opens instead of waiting the full hour
+        assert_eq!(
+            schedule.next_after(at(2026, 10, 19, 7, 30)),
+            at(2026, 10, 19, 8, 0)
+        );
+        // Saturday: fallback interval
+        assert_eq!(
+            schedule.next_after(at(2026, 10, 17, 12, 0)),
+            at(2026, 10, 17, 13, 0)
+        );
+    }
+
+    #[test]
+    fn several_windows_are_not_cron() {
+        let schedule = Schedule::parse("mon 8h-10h 5m; sun 1h", Duration::from_secs(3600)).unwrap();
+        assert!(matches!(schedule, Schedule::Windows { ref windows, .. } if windows.len() == 2));
+    }
+
+    #[test]
+    fn quiet_hours_end_after() {
+        let quiet = QuietHours {
+            start: NaiveTime::from_hms_opt(23, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+        };
+        assert!(quiet.contains(at(2026, 10, 17, 2, 0)));
+        assert!(!quiet.contains(at(2026, 10, 17, 7, 0)));
+        assert_eq!(
+            quiet.end_after(at(2026, 10, 17, 2, 0)),
+            at(2026, 10, 17, 7, 0)
+        );
+
+        // 24:00 ends at midnight
+        let (start, end) = parse_time_range("22:00-24:00").unwrap();
+        let late = QuietHours { start, end };
+        assert!(late.contains(at(2026, 10, 17, 23, 30)));
+        assert_eq!(
+            late.end_after(at(2026, 10, 17, 23, 30)),
+            at(2026, 10, 18, 0, 0)
+        );
+
+        // Empty, and would never end
+        assert!(parse_time_range("00:00-24:00").is_err());
+        assert!(parse_time_range("8h-8h").is_err());
+    }
+
+    #[test]
+    fn posting_periods_wrap_over_new_year() {
+        let period = Period::parse("12-15:01-10").unwrap();
+        assert!(period.contains(at(2026, 12, 20, 12, 0)));
+        assert!(period.contains(at(2027, 1, 5, 12, 0)));
+        assert!(!period.contains(at(2026, 11, 20, 12, 0)));
+    }
+}
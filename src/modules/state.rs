@@ -0,0 +1,495 @@
+use crate::constants::{
+    DAEMON_LOCK_FILE, DEFAULT_STATE_LOCK_TIMEOUT_SECS, MAX_CHANGE_TIMESTAMPS, STATE_FILE,
+    STATE_LOCK_FILE,
+};
+use crate::modules::api::{self, Activity, DetailActivity};
+use crate::modules::auth;
+use crate::modules::common::{self, Log};
+use crate::modules::error::UqgrdError;
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// Bumped whenever the layout of the state file changes.
+pub const STATE_VERSION: u32 = 2;
+
+// Semester and group recorded for entries migrated from the flat format,
+// which had neither
+const LEGACY_SEMESTER: u32 = 0;
+const LEGACY_GROUP: u32 = 0;
+
+/// One course as taken in a given semester and group.
+/// Stored as `"trimestre/sigle/groupe"` so it can be a JSON object key.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct CourseKey {
+    pub trimestre: u32,
+    pub sigle: String,
+    pub groupe: u32,
+}
+
+impl CourseKey {
+    pub fn new(trimestre: u32, activity: &Activity) -> Self {
+        Self {
+            trimestre,
+            sigle: activity.sigle.clone(),
+            groupe: activity.groupe,
+        }
+    }
+
+    fn legacy(&self) -> Self {
+        Self {
+            trimestre: LEGACY_SEMESTER,
+            sigle: self.sigle.clone(),
+            groupe: LEGACY_GROUP,
+        }
+    }
+
+    fn is_legacy(&self) -> bool {
+        self.trimestre == LEGACY_SEMESTER
+    }
+}
+
+impl fmt::Display for CourseKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}/{}", self.trimestre, self.sigle, self.groupe)
+    }
+}
+
+impl FromStr for CourseKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('/');
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(trimestre), Some(sigle), Some(groupe), None) => Ok(Self {
+                trimestre: trimestre
+                    .parse()
+                    .map_err(|_| format!("bad semester in key '{}'", s))?,
+                sigle: sigle.to_string(),
+                groupe: groupe
+                    .parse()
+                    .map_err(|_| format!("bad group in key '{}'", s))?,
+            }),
+            _ => Err(format!("'{}' is not a trimestre/sigle/groupe key", s)),
+        }
+    }
+}
+
+impl From<CourseKey> for String {
+    fn from(key: CourseKey) -> Self {
+        key.to_string()
+    }
+}
+
+impl TryFrom<String> for CourseKey {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CourseState {
+    pub total: Option<f64>,
+    pub note: Option<String>,
+    // Titles of the evaluations already posted, to spot newly graded ones
+    #[serde(default)]
+    pub posted: Vec<String>,
+    // When changes were detected, most recent last; feeds adaptive polling
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changed_at: Vec<DateTime<Utc>>,
+    // Could not be fetched while recording a baseline: the next successful
+    // fetch records it silently instead of comparing against nothing
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub unfetched: bool,
+}
+
+impl CourseState {
+    pub fn from_detail(data: &DetailActivity) -> Self {
+        Self {
+            total: data.total,
+            note: data.note.clone(),
+            posted: posted_titles(data),
+            changed_at: Vec::new(),
+            unfetched: false,
+        }
+    }
+
+    /// Current grades recorded as a baseline, keeping the change timestamps of `previous`.
+    pub fn recorded(previous: Option<&CourseState>, data: &DetailActivity) -> Self {
+        Self {
+            changed_at: previous.map(|p| p.changed_at.clone()).unwrap_or_default(),
+            ..Self::from_detail(data)
+        }
+    }
+
+    /// Stand-in for a course whose details failed during a baseline.
+    pub fn unfetched(previous: Option<&CourseState>) -> Self {
+        Self {
+            total: None,
+            note: None,
+            posted: Vec::new(),
+            changed_at: previous.map(|p| p.changed_at.clone()).unwrap_or_default(),
+            unfetched: true,
+        }
+    }
+
+    /// New state for a changed course, keeping the change timestamps of `previous`.
+    pub fn changed(previous: Option<&CourseState>, data: &DetailActivity) -> Self {
+        let mut changed_at = previous.map(|p| p.changed_at.clone()).unwrap_or_default();
+        changed_at.push(Utc::now());
+        let excess = changed_at.len().saturating_sub(MAX_CHANGE_TIMESTAMPS);
+        changed_at.drain(..excess);
+
+        Self {
+            changed_at,
+            ..Self::from_detail(data)
+        }
+    }
+}
+
+/// Last known grades of every tracked course, as saved in `grades_state.json`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GradesState {
+    pub version: u32,
+    pub courses: BTreeMap<CourseKey, CourseState>,
+    /// Semester the flat state file was written for. Migrated entries only
+    /// match courses of that semester.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub legacy_trimestre: Option<u32>,
+    /// Loaded from an older format and not yet written back.
+    #[serde(skip)]
+    pub migrated: bool,
+    /// No state file existed (first run, reset or corrupt file moved aside).
+    #[serde(skip)]
+    pub fresh: bool,
+}
+
+impl Default for GradesState {
+    fn default() -> Self {
+        Self {
+            version: STATE_VERSION,
+            courses: BTreeMap::new(),
+            legacy_trimestre: None,
+            migrated: false,
+            fresh: false,
+        }
+    }
+}
+
+impl GradesState {
+    /// Saved state of a course. Entries migrated from the flat format have
+    /// no semester or group, so they match any group of the same course in
+    /// the semester the old file was written for.
+    pub fn get(&self, key: &CourseKey) -> Option<&CourseState> {
+        self.courses.get(key).or_else(|| {
+            self.legacy_key(key)
+                .and_then(|legacy| self.courses.get(&legacy))
+        })
+    }
+
+    /// Records a course, replacing its migrated entry if there was one.
+    pub fn insert(&mut self, key: CourseKey, course: CourseState) {
+        if let Some(legacy) = self.legacy_key(&key) {
+            self.remove_legacy(&legacy);
+        }
+        self.courses.insert(key, course);
+    }
+
+    /// Re-files a migrated entry under the real semester and group of `key`;
+    /// returns whether anything moved.
+    pub fn adopt(&mut self, key: &CourseKey) -> bool {
+        if self.courses.contains_key(key) {
+            return false;
+        }
+        let Some(legacy) = self.legacy_key(key) else {
+            return false;
+        };
+        match self.remove_legacy(&legacy) {
+            Some(course) => {
+                self.courses.insert(key.clone(), course);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Migrated entry that `key` may resolve to, if it belongs to the legacy semester
+    fn legacy_key(&self, key: &CourseKey) -> Option<CourseKey> {
+        (self.legacy_trimestre == Some(key.trimestre) && !key.is_legacy()).then(|| key.legacy())
+    }
+
+    fn remove_legacy(&mut self, legacy: &CourseKey) -> Option<CourseState> {
+        let course = self.courses.remove(legacy);
+        if !self.courses.keys().any(CourseKey::is_legacy) {
+            self.legacy_trimestre = None;
+        }
+        course
+    }
+
+    /// Whether the course still has to be recorded silently (see `CourseState::unfetched`).
+    pub fn awaits_baseline(&self, key: &CourseKey) -> bool {
+        self.get(key).is_some_and(|course| course.unfetched)
+    }
+
+    pub fn len(&self) -> usize {
+        self.courses.len()
+    }
+
+    /// Every recorded change timestamp, across all courses.
+    pub fn change_timestamps(&self) -> Vec<DateTime<Utc>> {
+        self.courses
+            .values()
+            .flat_map(|course| course.changed_at.iter().copied())
+            .collect()
+    }
+}
+
+fn posted_titles(data: &DetailActivity) -> Vec<String> {
+    data.evaluations
+        .iter()
+        .filter(|e| e.is_posted())
+        .map(|e| e.titre.clone())
+        .collect()
+}
+
+/// Exclusive advisory lock on a file of the config directory, released on drop.
+pub struct DirLock {
+    _file: File,
+}
+
+impl DirLock {
+    /// Waits up to `STATE_LOCK_TIMEOUT` seconds for the state lock, so a daemon
+    /// cycle and commands like `diff` never read or write the state at the same time.
+    pub async fn state() -> Result<Self, UqgrdError> {
+        Self::state_logged(&Log::default()).await
+    }
+
+    /// Like `state`, with the waiting notice sent to `log`.
+    pub async fn state_logged(log: &Log) -> Result<Self, UqgrdError> {
+        let file = open_lock_file(STATE_LOCK_FILE)?;
+        let timeout = common::env_or("STATE_LOCK_TIMEOUT", DEFAULT_STATE_LOCK_TIMEOUT_SECS);
+        let deadline = Instant::now() + Duration::from_secs(timeout);
+        let mut waiting = false;
+
+        loop {
+            match file.try_lock() {
+                Ok(()) => return Ok(Self { _file: file }),
+                Err(TryLockError::WouldBlock) if Instant::now() < deadline => {
+                    if !waiting {
+                        log.emit(
+                            "⏳ Waiting for another uqgrd process to release the state..."
+                                .to_string(),
+                        );
+                        waiting = true;
+                    }
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                }
+                Err(TryLockError::WouldBlock) => {
+                    return Err(UqgrdError::Locked(format!(
+                        "still held by another uqgrd process after {}s",
+                        timeout
+                    )))
+                }
+                Err(TryLockError::Error(e)) => return Err(UqgrdError::Io(e.to_string())),
+            }
+        }
+    }
+
+    /// Fails right away if another daemon is already running on this config directory.
+    pub fn daemon() -> Result<Self, UqgrdError> {
+        let file = open_lock_file(DAEMON_LOCK_FILE)?;
+        match file.try_lock() {
+            Ok(()) => Ok(Self { _file: file }),
+            Err(TryLockError::WouldBlock) => Err(UqgrdError::Locked(
+                "another uqgrd daemon is already running".into(),
+            )),
+            Err(TryLockError::Error(e)) => Err(UqgrdError::Io(e.to_string())),
+        }
+    }
+}
+
+fn open_lock_file(name: &str) -> Result<File, UqgrdError> {
+    let config_dir = auth::get_config_dir()?;
+    fs::create_dir_all(&config_dir).map_err(|e| UqgrdError::Io(e.to_string()))?;
+
+    OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(config_dir.join(name))
+        .map_err(|e| UqgrdError::Io(e.to_string()))
+}
+
+pub fn load_state() -> Result<GradesState, UqgrdError> {
+    read_state()?.map_err(|reason| UqgrdError::Io(format!("Corrupt state file: {}", reason)))
+}
+
+/// Like `load_state`, but a corrupt file is moved aside (kept as a backup)
+/// and replaced by an empty state instead of failing every cycle.
+pub fn load_or_recover_state() -> Result<GradesState, UqgrdError> {
+    match read_state()? {
+        Ok(state) => Ok(state),
+        Err(reason) => {
+            let backup = common::move_aside(&auth::get_config_dir()?.join(STATE_FILE))?;
+
+            eprintln!("⚠️  State file was corrupt ({})", reason);
+            eprintln!("   Moved it to {} and starting fresh", backup.display());
+            Ok(GradesState {
+                fresh: true,
+                ..GradesState::default()
+            })
+        }
+    }
+}
+
+// The outer error is a real failure; the inner one means the file is unreadable
+fn read_state() -> Result<Result<GradesState, String>, UqgrdError> {
+    let config_dir = auth::get_config_dir()?;
+    let state_path = config_dir.join(STATE_FILE);
+
+    if !state_path.exists() {
+        return Ok(Ok(GradesState {
+            fresh: true,
+            ..GradesState::default()
+        }));
+    }
+
+    let content = fs::read_to_string(&state_path).map_err(|e| UqgrdError::Io(e.to_string()))?;
+    let raw: Value = match serde_json::from_str(&content) {
+        Ok(raw) => raw,
+        Err(e) => return Ok(Err(e.to_string())),
+    };
+
+    match raw.get("version").and_then(Value::as_u64) {
+        Some(v) if v > STATE_VERSION as u64 => Err(UqgrdError::Io(format!(
+            "State file version {} is newer than this uqgrd supports ({})",
+            v, STATE_VERSION
+        ))),
+        Some(_) => Ok(serde_json::from_value(raw).map_err(|e| e.to_string())),
+        None => Ok(migrate_flat(raw, written_for(&state_path))),
+    }
+}
+
+// The daemon only tracked the semester in session when it last wrote the file
+fn written_for(path: &Path) -> u32 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map(|modified| api::semester_code_at(DateTime::<Local>::from(modified)))
+        .unwrap_or_else(|_| api::get_current_semester_code())
+}
+
+// Version 1 was a flat `sigle -> CourseState` map. Entries are kept under a
+// placeholder semester and group, resolved only against `trimestre`, so a
+// course retaken in a later semester never matches them.
+fn migrate_flat(raw: Value, trimestre: u32) -> Result<GradesState, String> {
+    let flat: HashMap<String, CourseState> =
+        serde_json::from_value(raw).map_err(|e| e.to_string())?;
+
+    let mut state = GradesState {
+        legacy_trimestre: (!flat.is_empty()).then_some(trimestre),
+        migrated: true,
+        ..GradesState::default()
+    };
+    for (sigle, course) in flat {
+        let key = CourseKey {
+            trimestre: LEGACY_SEMESTER,
+            sigle,
+            groupe: LEGACY_GROUP,
+        };
+        state.courses.insert(key, course);
+    }
+
+    Ok(state)
+}
+
+/// Deletes the state file, so the next check starts from a fresh baseline.
+pub fn clear_state() -> Result<(), UqgrdError> {
+    let state_path = auth::get_config_dir()?.join(STATE_FILE);
+    match fs::remove_file(state_path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(UqgrdError::Io(e.to_string())),
+    }
+}
+
+/// Written atomically, so a crash mid-write leaves the previous state intact.
+pub fn save_state(state: &GradesState) -> Result<(), UqgrdError> {
+    let state_path = auth::get_config_dir()?.join(STATE_FILE);
+    let json = serde_json::to_string_pretty(state).map_err(|e| UqgrdError::Io(e.to_string()))?;
+    common::write_atomic(&state_path, json.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn key(spec: &str) -> CourseKey {
+        spec.parse().unwrap()
+    }
+
+    fn flat_state() -> GradesState {
+        let raw = json!({ "INF1120": { "total": 81.5, "note": "A-", "posted": ["TP1"] } });
+        migrate_flat(raw, 20263).unwrap()
+    }
+
+    #[test]
+    fn course_key_round_trips() {
+        let k = key("20263/INF1120/10");
+        assert_eq!(
+            (k.trimestre, k.sigle.as_str(), k.groupe),
+            (20263, "INF1120", 10)
+        );
+        assert_eq!(k.to_string(), "20263/INF1120/10");
+        assert!("INF1120".parse::<CourseKey>().is_err());
+        assert!("20263/INF1120/10/2".parse::<CourseKey>().is_err());
+    }
+
+    #[test]
+    fn flat_state_is_adopted_in_its_semester() {
+        let mut state = flat_state();
+        assert!(state.migrated);
+        assert_eq!(state.legacy_trimestre, Some(20263));
+
+        // Any group of the course in the semester the file was written for
+        let current = key("20263/INF1120/20");
+        assert_eq!(state.get(&current).and_then(|c| c.total), Some(81.5));
+
+        assert!(state.adopt(&current));
+        assert!(!state.adopt(&current));
+        assert_eq!(state.courses.keys().collect::<Vec<_>>(), [&current]);
+        assert_eq!(state.get(&current).unwrap().posted, ["TP1"]);
+        // Nothing left to resolve
+        assert_eq!(state.legacy_trimestre, None);
+        assert!(state.get(&key("20263/INF1120/10")).is_none());
+    }
+
+    #[test]
+    fn retaken_course_does_not_match_legacy_entry() {
+        let mut state = flat_state();
+        let retaken = key("20271/INF1120/10");
+
+        assert!(state.get(&retaken).is_none());
+        assert!(!state.adopt(&retaken));
+
+        // Recording the retake leaves the legacy entry for its own semester
+        let data: DetailActivity =
+            serde_json::from_value(json!({ "total": 60.0, "note": "C" })).unwrap();
+        state.insert(retaken.clone(), CourseState::from_detail(&data));
+        assert_eq!(state.len(), 2);
+        assert_eq!(state.legacy_trimestre, Some(20263));
+        assert_eq!(
+            state.get(&key("20263/INF1120/10")).and_then(|c| c.total),
+            Some(81.5)
+        );
+    }
+}
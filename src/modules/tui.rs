@@ -0,0 +1,459 @@
+use crate::modules::api::{format_semester_name, ApiClient, DetailActivity, SemesterResult};
+use crate::modules::common::Log;
+use crate::modules::daemon;
+use crate::modules::display::fmt_percent;
+use crate::modules::error::UqgrdError;
+use crate::modules::state::{self, CourseKey, DirLock, GradesState};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Cell, List, ListItem, ListState, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::HashMap;
+use std::time::Duration;
+
+#[derive(PartialEq)]
+enum Focus {
+    Semesters,
+    Courses,
+    Evaluations,
+}
+
+// (trimestre, program index) -> details of every activity, in transcript order
+type DetailsCache = HashMap<(u32, usize), Vec<Result<DetailActivity, String>>>;
+
+struct App {
+    transcript: Vec<SemesterResult>,
+    // Flattened (semester index, program index) pairs shown in the left pane
+    entries: Vec<(usize, usize)>,
+    semesters: ListState,
+    courses: TableState,
+    evaluations: TableState,
+    focus: Focus,
+    details: DetailsCache,
+    // Daemon's last saved grades, used to highlight what changed since
+    saved: GradesState,
+    status: String,
+    // Client messages (retries, re-login), shown in the status line since
+    // writing to stderr would garble the screen
+    log: Log,
+}
+
+impl App {
+    fn new(transcript: Vec<SemesterResult>, saved: GradesState, log: Log) -> Self {
+        let entries = entries_of(&transcript);
+        let mut semesters = ListState::default();
+        if !entries.is_empty() {
+            semesters.select(Some(0));
+        }
+
+        Self {
+            transcript,
+            entries,
+            semesters,
+            courses: TableState::default(),
+            evaluations: TableState::default(),
+            focus: Focus::Semesters,
+            details: HashMap::new(),
+            saved,
+            status: String::new(),
+            log,
+        }
+    }
+
+    // Appends the latest client message to the status line
+    fn show_log(&mut self) {
+        if let Some(last) = self.log.take().pop() {
+            self.status = if self.status.is_empty() {
+                last
+            } else {
+                format!("{} · {}", self.status, last)
+            };
+        }
+    }
+
+    fn selected_key(&self) -> Option<(u32, usize)> {
+        let (sem, prog) = *self.entries.get(self.semesters.selected()?)?;
+        Some((self.transcript[sem].trimestre, prog))
+    }
+
+    fn selected_program(&self) -> Option<(&SemesterResult, usize)> {
+        let (sem, prog) = *self.entries.get(self.semesters.selected()?)?;
+        Some((&self.transcript[sem], prog))
+    }
+
+    fn selected_detail(&self) -> Option<&DetailActivity> {
+        let key = self.selected_key()?;
+        self.details
+            .get(&key)?
+            .get(self.courses.selected()?)?
+            .as_ref()
+            .ok()
+    }
+
+    async fn load_selected(&mut self, client: &ApiClient, force: bool) {
+        let Some(key) = self.selected_key() else {
+            return;
+        };
+        if !force && self.details.contains_key(&key) {
+            return;
+        }
+        let Some((sem, prog)) = self.selected_program() else {
+            return;
+        };
+
+        let trimestre = sem.trimestre;
+        let activities = sem.programmes[prog].activites.clone();
+        let results = client
+            .fetch_all_course_details(trimestre, &activities)
+            .await;
+
+        let failures = results.iter().filter(|r| r.is_err()).count();
+        self.status = if failures > 0 {
+            format!("{} course(s) could not be loaded", failures)
+        } else {
+            format!("Loaded {}", format_semester_name(trimestre))
+        };
+        self.details.insert(
+            key,
+            results
+                .into_iter()
+                .map(|r| r.map_err(|e| e.to_string()))
+                .collect(),
+        );
+        self.show_log();
+    }
+
+    async fn refresh(&mut self, client: &ApiClient) {
+        match client.fetch_transcript().await {
+            Ok(transcript) => {
+                self.entries = entries_of(&transcript);
+                self.transcript = transcript;
+                self.details.clear();
+                if self
+                    .semesters
+                    .selected()
+                    .is_none_or(|i| i >= self.entries.len())
+                {
+                    self.semesters
+                        .select((!self.entries.is_empty()).then_some(0));
+                }
+            }
+            Err(e) => self.status = format!("Refresh failed: {}", e),
+        }
+        self.saved = load_saved(&self.log).await;
+        self.load_selected(client, true).await;
+    }
+
+    fn move_selection(&mut self, delta: i32) {
+        let (state_len, current) = match self.focus {
+            Focus::Semesters => (self.entries.len(), self.semesters.selected()),
+            Focus::Courses => (
+                self.selected_program()
+                    .map(|(sem, prog)| sem.programmes[prog].activites.len())
+                    .unwrap_or(0),
+                self.courses.selected(),
+            ),
+            Focus::Evaluations => (
+                self.selected_detail()
+                    .map(|d| d.evaluations.len())
+                    .unwrap_or(0),
+                self.evaluations.selected(),
+            ),
+        };
+        if state_len == 0 {
+            return;
+        }
+
+        let next = (current.unwrap_or(0) as i32 + delta).clamp(0, state_len as i32 - 1) as usize;
+        match self.focus {
+            Focus::Semesters => {
+                self.semesters.select(Some(next));
+                self.courses.select(None);
+            }
+            Focus::Courses => self.courses.select(Some(next)),
+            Focus::Evaluations => self.evaluations.select(Some(next)),
+        }
+    }
+}
+
+// Daemon state read under the state lock; empty when missing or unreadable
+async fn load_saved(log: &Log) -> GradesState {
+    match DirLock::state_logged(log).await {
+        Ok(_lock) => state::load_state().unwrap_or_default(),
+        Err(_) => GradesState::default(),
+    }
+}
+
+fn entries_of(transcript: &[SemesterResult]) -> Vec<(usize, usize)> {
+    transcript
+        .iter()
+        .enumerate()
+        .flat_map(|(s, sem)| (0..sem.programmes.len()).map(move |p| (s, p)))
+        .collect()
+}
+
+/// Full-screen grade browser. Blocks until the user quits.
+pub async fn run(client: ApiClient) -> Result<(), UqgrdError> {
+    let log = Log::buffered();
+    let client = client.with_log(log.clone());
+    let transcript = client.fetch_transcript().await?;
+    let saved = load_saved(&log).await;
+    let mut app = App::new(transcript, saved, log);
+    app.show_log();
+
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut app, &client).await;
+    ratatui::restore();
+    result
+}
+
+async fn event_loop(
+    terminal: &mut DefaultTerminal,
+    app: &mut App,
+    client: &ApiClient,
+) -> Result<(), UqgrdError> {
+    let io_err = |e: std::io::Error| UqgrdError::Io(e.to_string());
+
+    app.status = "Loading...".to_string();
+    terminal.draw(|f| draw(f, app)).map_err(io_err)?;
+    app.load_selected(client, false).await;
+
+    loop {
+        terminal.draw(|f| draw(f, app)).map_err(io_err)?;
+
+        if !event::poll(Duration::from_millis(250)).map_err(io_err)? {
+            continue;
+        }
+        let Event::Key(key) = event::read().map_err(io_err)? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        match key.code {
+            KeyCode::Char('q') => return Ok(()),
+            KeyCode::Up | KeyCode::Char('k') => app.move_selection(-1),
+            KeyCode::Down | KeyCode::Char('j') => app.move_selection(1),
+            KeyCode::Char('r') => {
+                app.status = "Refreshing...".to_string();
+                terminal.draw(|f| draw(f, app)).map_err(io_err)?;
+                app.refresh(client).await;
+            }
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => match app.focus {
+                Focus::Semesters => {
+                    app.focus = Focus::Courses;
+                    app.courses.select(Some(0));
+                }
+                Focus::Courses if app.selected_detail().is_some() => {
+                    app.focus = Focus::Evaluations;
+                    app.evaluations.select(Some(0));
+                }
+                _ => {}
+            },
+            KeyCode::Esc | KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => {
+                app.focus = match app.focus {
+                    Focus::Evaluations => Focus::Courses,
+                    _ => Focus::Semesters,
+                };
+            }
+            _ => {}
+        }
+
+        // Lazily fetch the details of a newly highlighted semester
+        if app
+            .selected_key()
+            .is_some_and(|k| !app.details.contains_key(&k))
+        {
+            app.status = "Loading...".to_string();
+            terminal.draw(|f| draw(f, app)).map_err(io_err)?;
+            app.load_selected(client, false).await;
+        }
+    }
+}
+
+fn pane(title: &str, focused: bool) -> Block<'_> {
+    let style = if focused {
+        Style::default().fg(Color::Cyan)
+    } else {
+        Style::default()
+    };
+    Block::default()
+        .borders(Borders::ALL)
+        .border_style(style)
+        .title(title)
+}
+
+fn draw(frame: &mut Frame, app: &mut App) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .split(frame.area());
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
+        .split(rows[0]);
+
+    // Left: semester / program list
+    let items: Vec<ListItem> = app
+        .entries
+        .iter()
+        .map(|&(s, p)| {
+            let sem = &app.transcript[s];
+            ListItem::new(format!(
+                "{} - {}",
+                format_semester_name(sem.trimestre),
+                sem.programmes[p].titre_programme
+            ))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(pane(" Semesters ", app.focus == Focus::Semesters))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    frame.render_stateful_widget(list, columns[0], &mut app.semesters);
+
+    // Right: courses, or the evaluations of the selected course
+    if app.focus == Focus::Evaluations {
+        draw_evaluations(frame, app, columns[1]);
+    } else {
+        draw_courses(frame, app, columns[1]);
+    }
+
+    let help = "↑↓ move · Enter open · Esc back · r refresh · q quit";
+    let status = if app.status.is_empty() {
+        help.to_string()
+    } else {
+        format!("{}  |  {}", app.status, help)
+    };
+    frame.render_widget(Line::from(status), rows[1]);
+}
+
+fn draw_courses(frame: &mut Frame, app: &mut App, area: ratatui::layout::Rect) {
+    let mut rows = Vec::new();
+
+    if let Some((sem, prog)) = app.selected_program() {
+        let details = app.selected_key().and_then(|k| app.details.get(&k));
+
+        for (i, activity) in sem.programmes[prog].activites.iter().enumerate() {
+            let (total, note, changed) = match details.and_then(|d| d.get(i)) {
+                Some(Ok(det)) => {
+                    // Courses the daemon never recorded (past semesters, no
+                    // state file yet) have nothing to differ from
+                    let key = CourseKey::new(sem.trimestre, activity);
+                    let changed = app.saved.get(&key).is_some()
+                        && daemon::has_grade_changed(&app.saved, &key, det);
+                    (
+                        fmt_percent(det.total),
+                        det.note.clone().unwrap_or("N/A".to_string()),
+                        changed,
+                    )
+                }
+                Some(Err(_)) => ("ERROR".to_string(), "---".to_string(), false),
+                None => ("...".to_string(), String::new(), false),
+            };
+
+            let style = if changed {
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            rows.push(
+                Row::new(vec![
+                    Cell::from(if changed { "●" } else { " " }),
+                    Cell::from(activity.sigle.clone()),
+                    Cell::from(activity.titre.clone()),
+                    Cell::from(total),
+                    Cell::from(note),
+                ])
+                .style(style),
+            );
+        }
+    }
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(1),
+            Constraint::Length(10),
+            Constraint::Min(20),
+            Constraint::Length(10),
+            Constraint::Length(5),
+        ],
+    )
+    .header(
+        Row::new(vec!["", "Sigle", "Title", "Total", "Grade"])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+    )
+    .block(pane(
+        " Courses (● changed since last daemon check) ",
+        app.focus == Focus::Courses,
+    ))
+    .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+    frame.render_stateful_widget(table, area, &mut app.courses);
+}
+
+fn draw_evaluations(frame: &mut Frame, app: &mut App, area: ratatui::layout::Rect) {
+    let mut rows = Vec::new();
+    let mut title = " Evaluations ".to_string();
+
+    if let Some(detail) = app.selected_detail() {
+        title = format!(
+            " Evaluations — total {} ({}) ",
+            fmt_percent(detail.total),
+            detail.note.as_deref().unwrap_or("N/A")
+        );
+
+        for eval in &detail.evaluations {
+            let stats = eval.stats();
+            rows.push(Row::new(vec![
+                eval.titre.clone(),
+                eval.ponderation
+                    .map(|w| format!("{:.1}%", w))
+                    .unwrap_or("N/A".into()),
+                eval.percentage()
+                    .map(|p| format!("{:.1}%", p))
+                    .unwrap_or("—".into()),
+                eval.contribution()
+                    .map(|c| format!("{:.2}", c))
+                    .unwrap_or("—".into()),
+                stats
+                    .mean
+                    .map(|m| format!("{:.1}%", m))
+                    .unwrap_or("—".into()),
+                eval.date_diffusion.clone().unwrap_or("—".into()),
+            ]));
+        }
+    }
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(20),
+            Constraint::Length(7),
+            Constraint::Length(7),
+            Constraint::Length(12),
+            Constraint::Length(7),
+            Constraint::Length(10),
+        ],
+    )
+    .header(
+        Row::new(vec![
+            "Evaluation",
+            "Weight",
+            "Score",
+            "Contribution",
+            "Mean",
+            "Posted",
+        ])
+        .style(Style::default().add_modifier(Modifier::BOLD)),
+    )
+    .block(pane(&title, true))
+    .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+    frame.render_stateful_widget(table, area, &mut app.evaluations);
+}
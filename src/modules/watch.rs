@@ -0,0 +1,123 @@
+use crate::modules::api::{format_semester_name, ApiClient, Programme};
+use crate::modules::daemon;
+use crate::modules::display;
+use crate::modules::error::UqgrdError;
+use crate::modules::state::{CourseKey, CourseState, GradesState};
+use chrono::Local;
+use std::io::{self, Write};
+use std::process::Command;
+use std::time::Duration;
+
+pub struct WatchOptions {
+    pub interval: Duration,
+    /// Ring the terminal bell when something changes.
+    pub bell: bool,
+    /// Fire a desktop notification when something changes.
+    pub notify: bool,
+}
+
+/// Re-fetches one semester/program on an interval and redraws the table in
+/// place until Ctrl+C. Changes are detected with the daemon's comparison logic.
+pub async fn run(
+    client: &ApiClient,
+    semester: u32,
+    program: &Programme,
+    options: &WatchOptions,
+) -> Result<(), UqgrdError> {
+    // None until the first fetch, which only sets the baseline
+    let mut previous: Option<GradesState> = None;
+    let mut last_change: Option<String> = None;
+
+    // One future for the whole run, so a Ctrl+C pressed while fetching is
+    // not lost between two selects
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+
+    loop {
+        let details = tokio::select! {
+            details = client.fetch_all_course_details(semester, &program.activites) => details,
+            _ = &mut ctrl_c => break,
+        };
+
+        let mut changed = vec![false; program.activites.len()];
+        let mut next = previous.clone().unwrap_or_default();
+
+        for (i, (activity, detail)) in program.activites.iter().zip(&details).enumerate() {
+            // Courses that failed this round keep their last known value
+            let Ok(det) = detail else { continue };
+            let key = CourseKey::new(semester, activity);
+
+            if let Some(prev) = &previous {
+                changed[i] = daemon::has_grade_changed(prev, &key, det);
+            }
+            next.insert(key, CourseState::from_detail(det));
+        }
+
+        let changed_sigles: Vec<&str> = program
+            .activites
+            .iter()
+            .zip(&changed)
+            .filter(|(_, c)| **c)
+            .map(|(a, _)| a.sigle.as_str())
+            .collect();
+        let now = Local::now().format("%H:%M:%S").to_string();
+        if !changed_sigles.is_empty() {
+            last_change = Some(format!("{} at {}", changed_sigles.join(", "), now));
+        }
+
+        // Clear the screen and redraw from the top-left corner
+        print!("\x1b[2J\x1b[H");
+        println!(
+            "👀 Watching {} - {} (every {} min, Ctrl+C to stop)",
+            format_semester_name(semester),
+            program.titre_programme,
+            options.interval.as_secs() / 60
+        );
+        println!("   Last refresh: {}\n", now);
+        display::print_grades_table(&program.activites, &details, &changed);
+        if let Some(change) = &last_change {
+            println!("\n🔔 Last change: {}", change);
+        }
+
+        if !changed_sigles.is_empty() {
+            if options.bell {
+                print!("\x07");
+            }
+            if options.notify {
+                desktop_notify(
+                    "UQAM grade update",
+                    &format!("Changed: {}", changed_sigles.join(", ")),
+                );
+            }
+        }
+        let _ = io::stdout().flush();
+
+        previous = Some(next);
+
+        tokio::select! {
+            _ = tokio::time::sleep(options.interval) => {}
+            _ = &mut ctrl_c => break,
+        }
+    }
+
+    println!("\n👋 Stopped watching.");
+    Ok(())
+}
+
+fn desktop_notify(summary: &str, body: &str) {
+    #[cfg(target_os = "macos")]
+    let result = Command::new("osascript")
+        .arg("-e")
+        .arg(format!(
+            "display notification {:?} with title {:?}",
+            body, summary
+        ))
+        .status();
+
+    #[cfg(not(target_os = "macos"))]
+    let result = Command::new("notify-send").arg(summary).arg(body).status();
+
+    if let Err(e) = result {
+        eprintln!("⚠️  Desktop notification failed: {}", e);
+    }
+}
@@ -0,0 +1,157 @@
+use crate::modules::api::Evaluation;
+use crate::modules::error::UqgrdError;
+
+// Common UQAM conversion grid (percent cutoffs). Instructors may publish a
+// different one in their course outline; pass `--target <percent>` then.
+pub const LETTER_CUTOFFS: &[(&str, f64)] = &[
+    ("A+", 90.0),
+    ("A", 85.0),
+    ("A-", 80.0),
+    ("B+", 77.0),
+    ("B", 73.0),
+    ("B-", 70.0),
+    ("C+", 65.0),
+    ("C", 60.0),
+    ("C-", 57.0),
+    ("D+", 54.0),
+    ("D", 50.0),
+];
+
+#[derive(Debug, Clone)]
+pub struct Target {
+    pub label: String,
+    /// Final total required, in percent.
+    pub cutoff: f64,
+}
+
+/// Hypothetical score (in percent) for an evaluation that isn't posted yet.
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    pub name: String,
+    pub score: f64,
+}
+
+/// Accepts a letter from `LETTER_CUTOFFS` or a percentage (`82`, `82.5%`).
+pub fn parse_target(input: &str) -> Result<Target, String> {
+    let trimmed = input.trim();
+
+    if let Some((letter, cutoff)) = LETTER_CUTOFFS
+        .iter()
+        .find(|(l, _)| l.eq_ignore_ascii_case(trimmed))
+    {
+        return Ok(Target {
+            label: letter.to_string(),
+            cutoff: *cutoff,
+        });
+    }
+
+    match trimmed
+        .trim_end_matches('%')
+        .replace(',', ".")
+        .parse::<f64>()
+    {
+        Ok(cutoff) if (0.0..=100.0).contains(&cutoff) => Ok(Target {
+            label: format!("{}%", cutoff),
+            cutoff,
+        }),
+        _ => Err(format!(
+            "'{}' is not a letter grade (A+ ... D) or a percentage",
+            input
+        )),
+    }
+}
+
+/// Parses `NAME=SCORE`, e.g. `Final=85` (score in percent).
+pub fn parse_simulation(input: &str) -> Result<Simulation, String> {
+    let (name, score) = input
+        .rsplit_once('=')
+        .ok_or_else(|| format!("'{}' should look like NAME=SCORE", input))?;
+    let score: f64 = score
+        .trim()
+        .trim_end_matches('%')
+        .replace(',', ".")
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid score", score))?;
+
+    if !(0.0..=100.0).contains(&score) {
+        return Err(format!("Score {} must be between 0 and 100", score));
+    }
+
+    Ok(Simulation {
+        name: name.trim().to_string(),
+        score,
+    })
+}
+
+pub enum Outcome {
+    /// Reached even with 0% on everything left.
+    Guaranteed,
+    /// Out of reach even with 100% on everything left.
+    Unreachable,
+    /// Average needed on the remaining evaluations, in percent.
+    Needs(f64),
+}
+
+/// Points secured so far and the weight still up for grabs.
+#[derive(Debug, Default)]
+pub struct Projection {
+    pub earned: f64,
+    pub remaining_weight: f64,
+    /// Evaluations without a published weight, left out of the math.
+    pub unweighted: Vec<String>,
+}
+
+impl Projection {
+    pub fn new(evaluations: &[Evaluation], simulations: &[Simulation]) -> Result<Self, UqgrdError> {
+        // Every simulation must name an evaluation that is still open
+        for sim in simulations {
+            let matched = evaluations
+                .iter()
+                .any(|e| !e.is_posted() && e.titre.eq_ignore_ascii_case(&sim.name));
+            if !matched {
+                return Err(UqgrdError::Input(format!(
+                    "No unposted evaluation named '{}'",
+                    sim.name
+                )));
+            }
+        }
+
+        let mut projection = Projection::default();
+
+        for eval in evaluations {
+            let Some(weight) = eval.ponderation else {
+                projection.unweighted.push(eval.titre.clone());
+                continue;
+            };
+
+            let simulated = simulations
+                .iter()
+                .find(|s| !eval.is_posted() && s.name.eq_ignore_ascii_case(&eval.titre));
+
+            match (eval.percentage(), simulated) {
+                (Some(pct), _) => projection.earned += pct * weight / 100.0,
+                (None, Some(sim)) => projection.earned += sim.score * weight / 100.0,
+                (None, None) => projection.remaining_weight += weight,
+            }
+        }
+
+        Ok(projection)
+    }
+
+    pub fn outcome(&self, target: f64) -> Outcome {
+        let missing = target - self.earned;
+
+        if missing <= 0.0 {
+            Outcome::Guaranteed
+        } else if missing > self.remaining_weight {
+            Outcome::Unreachable
+        } else {
+            Outcome::Needs(missing / self.remaining_weight * 100.0)
+        }
+    }
+
+    /// Final total if every remaining evaluation is scored at `average` percent.
+    pub fn total_with(&self, average: f64) -> f64 {
+        self.earned + self.remaining_weight * average / 100.0
+    }
+}